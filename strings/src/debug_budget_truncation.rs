@@ -0,0 +1,456 @@
+use crate::debug_native_shape_truncation::NativeShapeTruncateDebug;
+use crate::debug_truncation::DebugFlags;
+use crate::debug_truncation_derive::TruncatedField;
+use crate::debug_truncation_options::TruncationOptions;
+use std::cell::Cell;
+use std::fmt::{Debug, Formatter};
+
+/// Pools of items and chars shared by every collection field formatted within one `TruncationBudgetScope`,
+/// so the whole debug string stays bounded however many collection fields a struct has.
+/// Fields draw from the pools in the order they are formatted. `max_total_chars` is a hard cap:
+/// a field which does not fit what is left is written as with no item shown, e.g. `(length=5)`,
+/// or as the marker alone, or as nothing, whichever is the longest that fits.
+/// Start a scope in each `fmt` call, so every call draws from full pools.
+/// ```rust
+/// use std::fmt::{Debug, Formatter};
+/// use strings::debug_budget_truncation::TruncationBudget;
+/// struct Order {
+///     items: Vec<i32>,
+///     refunds: Vec<i32>,
+///     budget: TruncationBudget,
+/// }
+/// impl Debug for Order {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+///         let scope = self.budget.scope();
+///         f.debug_struct("Order")
+///             .field("items", &scope.truncated(&self.items))
+///             .field("refunds", &scope.truncated(&self.refunds))
+///             .finish()
+///     }
+/// }
+/// let budget = TruncationBudget::new().max_total_items(3).min_items_per_field(1);
+/// let order = Order { items: vec![1, 2, 3], refunds: vec![4, 5], budget };
+/// assert_eq!(
+///     format!("{:?}", order),
+///     "Order { items: [1, 2, 3], refunds: (length=2 truncated to 1) [4]... }"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct TruncationBudget {
+    max_total_items: usize,
+    max_total_chars: Option<usize>,
+    min_items_per_field: usize,
+    min_chars_per_field: usize,
+    fields: usize,
+    options: TruncationOptions,
+}
+
+impl TruncationBudget {
+    /// Unlimited pools, with no minimum per field.
+    pub fn new() -> Self {
+        Self {
+            max_total_items: usize::MAX,
+            max_total_chars: None,
+            min_items_per_field: 0,
+            min_chars_per_field: 0,
+            fields: 1,
+            options: TruncationOptions::new(),
+        }
+    }
+
+    /// Show at most `max_total_items` items across all fields, apart from `min_items_per_field`,
+    /// unlimited by default.
+    pub fn max_total_items(mut self, max_total_items: usize) -> Self {
+        self.max_total_items = max_total_items;
+        self
+    }
+
+    /// Write at most `max_total_chars` chars across all fields, unlimited by default.
+    /// Each field shows as many items as fit what is left of the pool, counting its header,
+    /// delimiters, separators and marker, before any indentation added by a pretty formatter.
+    pub fn max_total_chars(mut self, max_total_chars: usize) -> Self {
+        self.max_total_chars = Some(max_total_chars);
+        self
+    }
+
+    /// Items each field may show even once the item pool is used up, `0` by default.
+    /// The fields still have to fit the char pool.
+    pub fn min_items_per_field(mut self, min_items_per_field: usize) -> Self {
+        self.min_items_per_field = min_items_per_field;
+        self
+    }
+
+    /// Chars held back from the pool for each of the `fields` fields not yet formatted, `0` by default,
+    /// so each may write at least that many, or an even share of what is left if that is fewer.
+    pub fn min_chars_per_field(mut self, min_chars_per_field: usize) -> Self {
+        self.min_chars_per_field = min_chars_per_field;
+        self
+    }
+
+    /// Number of fields formatted within each scope, for which `min_chars_per_field` is held back, `1` by default.
+    pub fn fields(mut self, fields: usize) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Truncate fields as specified by `options`, apart from the item and char limits
+    /// which are taken from the pools, so `options.max_total_chars` is ignored.
+    pub fn options(mut self, options: TruncationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Full pools for the fields formatted during one `fmt` call.
+    pub fn scope(&self) -> TruncationBudgetScope<'_> {
+        TruncationBudgetScope {
+            budget: self,
+            remaining_items: Cell::new(self.max_total_items),
+            remaining_chars: Cell::new(self.max_total_chars),
+            formatted_fields: Cell::new(0),
+        }
+    }
+}
+
+impl Default for TruncationBudget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What is left of the pools of a `TruncationBudget` during one `fmt` call.
+pub struct TruncationBudgetScope<'budget> {
+    budget: &'budget TruncationBudget,
+    remaining_items: Cell<usize>,
+    remaining_chars: Cell<Option<usize>>,
+    formatted_fields: Cell<usize>,
+}
+
+impl<'budget> TruncationBudgetScope<'budget> {
+    /// Items left in the pool.
+    pub fn remaining_items(&self) -> usize {
+        self.remaining_items.get()
+    }
+
+    /// Chars left in the pool, if limited.
+    pub fn remaining_chars(&self) -> Option<usize> {
+        self.remaining_chars.get()
+    }
+
+    /// Truncate `collection`, keeping its own delimiters, to what is left of the pools when formatted.
+    pub fn truncated<'scope, C: ?Sized>(
+        &'scope self,
+        collection: &'scope C,
+    ) -> BudgetedDebug<'scope, 'budget, C> {
+        BudgetedDebug {
+            collection,
+            scope: self,
+        }
+    }
+
+    /// Chars the next field may write: what is left once `min_chars_per_field` is held back
+    /// for the fields after it, or an even share of what is left if that is more.
+    fn next_field_chars(&self) -> Option<usize> {
+        let budget = self.budget;
+        let later_fields = budget
+            .fields
+            .saturating_sub(self.formatted_fields.get() + 1);
+        self.remaining_chars.get().map(|remaining_chars| {
+            let held_back = budget.min_chars_per_field.saturating_mul(later_fields);
+            remaining_chars
+                .saturating_sub(held_back)
+                .max(remaining_chars / (later_fields + 1))
+        })
+    }
+}
+
+/// Collection truncated to what is left of a `TruncationBudgetScope` when formatted.
+/// Formatting it again draws from the pools again.
+/// The format flags reach the items, apart from a precision, as the item limit comes from the pools.
+pub struct BudgetedDebug<'scope, 'budget, C: ?Sized> {
+    collection: &'scope C,
+    scope: &'scope TruncationBudgetScope<'budget>,
+}
+
+impl<'scope, 'budget, C> Debug for BudgetedDebug<'scope, 'budget, C>
+where
+    C: NativeShapeTruncateDebug + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let scope = self.scope;
+        let budget = scope.budget;
+        let remaining_items = scope.remaining_items.get();
+        let max_length = remaining_items.max(budget.min_items_per_field);
+        let max_chars = scope.next_field_chars();
+        let flags = DebugFlags::of(f).without_precision();
+        let render = |shown_length: usize| {
+            let mut options = budget.options.clone().max_length(shown_length);
+            options.max_total_chars = None;
+            let mut rendered = String::new();
+            flags.write(
+                &mut rendered,
+                &TruncatedField::new(self.collection, options),
+            )?;
+            Ok::<_, std::fmt::Error>(rendered)
+        };
+        let fits = |rendered: &str| match max_chars {
+            Some(max_chars) => rendered.chars().count() <= max_chars,
+            None => true,
+        };
+
+        // Every shown item takes at least one char, and fewer items never take more chars once truncated.
+        let most_shown_length = self
+            .collection
+            .native_length()
+            .min(max_length)
+            .min(max_chars.unwrap_or(usize::MAX));
+        let rendered = render(most_shown_length)?;
+        let (shown_length, rendered) = if fits(&rendered) {
+            (most_shown_length, rendered)
+        } else {
+            let mut fitting = (0, render(0)?);
+            let (mut low, mut high) = (1, most_shown_length);
+            while low < high {
+                let middle = low + (high - low) / 2;
+                let rendered = render(middle)?;
+                if fits(&rendered) {
+                    fitting = (middle, rendered);
+                    low = middle + 1;
+                } else {
+                    high = middle;
+                }
+            }
+            fitting
+        };
+        let rendered = if fits(&rendered) {
+            rendered
+        } else if fits(&budget.options.marker) {
+            budget.options.marker.to_string()
+        } else {
+            String::new()
+        };
+
+        scope
+            .remaining_items
+            .set(remaining_items.saturating_sub(shown_length));
+        if let Some(remaining_chars) = scope.remaining_chars.get() {
+            scope.remaining_chars.set(Some(
+                remaining_chars.saturating_sub(rendered.chars().count()),
+            ));
+        }
+        scope.formatted_fields.set(scope.formatted_fields.get() + 1);
+        f.write_str(&rendered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_truncation_budget {
+        use super::super::TruncationBudget;
+        use crate::debug_truncation_options::TruncationOptions;
+        use linked_hash_set::LinkedHashSet;
+        use std::collections::BTreeMap;
+        use std::fmt::{Debug, Formatter};
+
+        /// Example struct whose collection fields share one budget.
+        struct StructWithCollections {
+            vec: Vec<&'static str>,
+            hash_set: LinkedHashSet<i32>,
+            map: BTreeMap<i32, i32>,
+            budget: TruncationBudget,
+        }
+
+        impl Debug for StructWithCollections {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                let scope = self.budget.scope();
+                f.debug_struct("StructWithCollections")
+                    .field("vec", &scope.truncated(&self.vec))
+                    .field("hash_set", &scope.truncated(&self.hash_set))
+                    .field("map", &scope.truncated(&self.map))
+                    .finish()
+            }
+        }
+
+        fn struct_with_collections(budget: TruncationBudget) -> StructWithCollections {
+            StructWithCollections {
+                vec: vec!["abc", "def"],
+                hash_set: (1..=4).collect(),
+                map: (1..=4).map(|i| (i, -i)).collect(),
+                budget,
+            }
+        }
+
+        fn truncate_debug_string_for(budget: TruncationBudget) -> String {
+            format!("{:?}", struct_with_collections(budget))
+        }
+
+        #[test]
+        fn truncation_budget_unlimited() {
+            let result = truncate_debug_string_for(TruncationBudget::new());
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 vec: [\"abc\", \"def\"], \
+                 hash_set: {1, 2, 3, 4}, \
+                 map: {1: -1, 2: -2, 3: -3, 4: -4} }"
+            );
+        }
+
+        #[test]
+        fn truncation_budget_max_total_items() {
+            let result = truncate_debug_string_for(TruncationBudget::new().max_total_items(5));
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 vec: [\"abc\", \"def\"], \
+                 hash_set: (length=4 truncated to 3) {1, 2, 3}..., \
+                 map: (length=4) }"
+            );
+        }
+
+        #[test]
+        fn truncation_budget_min_items_per_field() {
+            let budget = TruncationBudget::new()
+                .max_total_items(3)
+                .min_items_per_field(1);
+            let result = truncate_debug_string_for(budget);
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 vec: [\"abc\", \"def\"], \
+                 hash_set: (length=4 truncated to 1) {1}..., \
+                 map: (length=4 truncated to 1) {1: -1}... }"
+            );
+        }
+
+        #[test]
+        fn truncation_budget_max_total_chars() {
+            let result = truncate_debug_string_for(TruncationBudget::new().max_total_chars(30));
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 vec: [\"abc\", \"def\"], \
+                 hash_set: {1, 2, 3, 4}, \
+                 map: ... }"
+            );
+        }
+
+        #[test]
+        fn truncation_budget_max_total_chars_is_a_hard_cap() {
+            let input = struct_with_collections(TruncationBudget::new());
+            for max_total_chars in 0..=60 {
+                let budget = TruncationBudget::new()
+                    .max_total_chars(max_total_chars)
+                    .min_items_per_field(1)
+                    .min_chars_per_field(10)
+                    .fields(3);
+                let scope = budget.scope();
+                let result = format!(
+                    "{:?}{:?}{:?}",
+                    scope.truncated(&input.vec),
+                    scope.truncated(&input.hash_set),
+                    scope.truncated(&input.map)
+                );
+
+                assert!(result.len() <= max_total_chars, "{}", result);
+            }
+        }
+
+        #[test]
+        fn truncation_budget_min_chars_per_field() {
+            let budget = TruncationBudget::new()
+                .max_total_chars(30)
+                .min_chars_per_field(10)
+                .fields(3);
+            let result = truncate_debug_string_for(budget);
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 vec: (length=2), \
+                 hash_set: (length=4), \
+                 map: (length=4) }"
+            );
+        }
+
+        #[test]
+        fn truncation_budget_max_total_chars_used_up() {
+            let result = truncate_debug_string_for(TruncationBudget::new().max_total_chars(3));
+
+            assert_eq!(
+                result,
+                "StructWithCollections { vec: ..., hash_set: , map:  }"
+            );
+        }
+
+        #[test]
+        fn truncation_budget_max_total_chars_counts_header() {
+            let budget = TruncationBudget::new()
+                .max_total_items(10)
+                .max_total_chars(40);
+            let scope = budget.scope();
+            let result = format!("{:?}", scope.truncated(&(1..=20).collect::<Vec<_>>()));
+
+            assert_eq!(result, "(length=20 truncated to 3) [1, 2, 3]...");
+            assert_eq!(scope.remaining_items(), 7);
+            assert_eq!(scope.remaining_chars(), Some(1));
+        }
+
+        #[test]
+        fn truncation_budget_with_options() {
+            let budget = TruncationBudget::new()
+                .max_total_items(3)
+                .options(TruncationOptions::new().show_header(false).marker("~"));
+            let result = truncate_debug_string_for(budget);
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 vec: [\"abc\", \"def\"], \
+                 hash_set: {1}~, \
+                 map: (length=4) }"
+            );
+        }
+
+        #[test]
+        fn truncation_budget_is_drawn_down() {
+            let budget = TruncationBudget::new()
+                .max_total_items(3)
+                .max_total_chars(100);
+            let scope = budget.scope();
+            let _ = format!("{:?}", scope.truncated(&[1, 2]));
+
+            assert_eq!(scope.remaining_items(), 1);
+            assert_eq!(scope.remaining_chars(), Some(94));
+            assert_eq!(budget.scope().remaining_items(), 3);
+        }
+
+        #[test]
+        fn truncation_budget_each_fmt_draws_from_full_pools() {
+            let input = struct_with_collections(TruncationBudget::new().max_total_items(5));
+
+            assert_eq!(format!("{:?}", input), format!("{:?}", input));
+        }
+
+        #[test]
+        fn truncation_budget_keeps_flags() {
+            let budget = TruncationBudget::new().max_total_chars(11);
+            let result = format!("{:x?}", budget.scope().truncated(&[255, 16, 1]));
+            let precise = format!("{:.1?}", budget.scope().truncated(&[0.5, 1.5]));
+
+            assert_eq!(result, "[ff, 10, 1]");
+            assert_eq!(precise, "[0.5, 1.5]");
+        }
+
+        #[test]
+        fn truncation_budget_pretty() {
+            let budget = TruncationBudget::new().max_total_items(1);
+            let result = format!("{:#?}", budget.scope().truncated(&[1, 2]));
+
+            assert_eq!(result, "(length=2 truncated to 1) [\n    1,\n    ...,\n]");
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use crate::debug_native_shape_truncation::NativeShapeTruncateDebug;
+use crate::debug_truncation::{
+    format_debug_map_truncated_to_max_length, format_debug_truncated_to_max_length,
+};
+use crate::debug_truncation_options::TruncationOptions;
+use std::fmt::{Debug, DebugStruct, Formatter};
+
+/// Field truncated to `options`, whatever the format precision.
+struct FixedTruncatedField<'collection, C: ?Sized> {
+    collection: &'collection C,
+    options: TruncationOptions,
+}
+
+impl<'collection, C> Debug for FixedTruncatedField<'collection, C>
+where
+    C: NativeShapeTruncateDebug + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.collection
+            .format_debug_native_truncated_with_options(f, &self.options)
+    }
+}
+
+/// Truncated fields for hand-written `Debug` impls using `Formatter::debug_struct`.
+/// ```rust
+/// use std::fmt::{Debug, Formatter};
+/// use strings::prelude::*;
+/// struct StructWithAVec {
+///     vec: Vec<i32>,
+/// }
+/// impl Debug for StructWithAVec {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+///         f.debug_struct("StructWithAVec")
+///             .field_truncated("vec", &self.vec, 2)
+///             .finish()
+///     }
+/// }
+/// assert_eq!(
+///     format!("{:?}", StructWithAVec { vec: vec![1, 2, 3] }),
+///     "StructWithAVec { vec: (length=3 truncated to 2) [1, 2]... }"
+/// );
+/// ```
+pub trait DebugStructTruncateExt {
+    /// Add a field holding `value` truncated to `max_length` items,
+    /// as `format_debug_exact_size_truncated_to_max_length` does but keeping the collection's own delimiters.
+    /// The format precision does not replace `max_length`.
+    fn field_truncated<C: NativeShapeTruncateDebug + ?Sized>(
+        &mut self,
+        name: &str,
+        value: &C,
+        max_length: usize,
+    ) -> &mut Self;
+}
+
+impl<'a, 'b: 'a> DebugStructTruncateExt for DebugStruct<'a, 'b> {
+    fn field_truncated<C: NativeShapeTruncateDebug + ?Sized>(
+        &mut self,
+        name: &str,
+        value: &C,
+        max_length: usize,
+    ) -> &mut Self {
+        let field = FixedTruncatedField {
+            collection: value,
+            options: TruncationOptions::new().max_length(max_length),
+        };
+        self.field(name, &field)
+    }
+}
+
+/// Truncated lists and maps for hand-written `Debug` impls, in place of `Formatter::debug_list`
+/// and `Formatter::debug_map`, as the header of `format_debug_exact_size_truncated_to_max_length`
+/// is written before the opening delimiter, which a builder has already written once it exists.
+/// ```rust
+/// use std::fmt::{Debug, Formatter};
+/// use strings::prelude::*;
+/// struct ListOfInts(Vec<i32>);
+/// impl Debug for ListOfInts {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+///         f.debug_list_truncated(&self.0, 2)
+///     }
+/// }
+/// assert_eq!(
+///     format!("{:?}", ListOfInts(vec![1, 2, 3])),
+///     "(length=3 truncated to 2) [1, 2]..."
+/// );
+/// ```
+pub trait FormatterTruncateExt {
+    /// Write `entries` as a list truncated to `max_length` items, e.g. `(length=3 truncated to 2) [1, 2]...`,
+    /// or `(length=3)` when no item is shown.
+    fn debug_list_truncated<D, ESI, ESII>(
+        &mut self,
+        entries: ESII,
+        max_length: usize,
+    ) -> Result<(), std::fmt::Error>
+    where
+        D: Debug,
+        ESI: ExactSizeIterator<Item = D>,
+        ESII: IntoIterator<IntoIter = ESI>;
+
+    /// Write `entries` as a map truncated to `max_length` entries, e.g. `(length=3 truncated to 1) {1: -1}...`,
+    /// or `(length=3)` when no entry is shown.
+    fn debug_map_truncated<K, V, ESI, ESII>(
+        &mut self,
+        entries: ESII,
+        max_length: usize,
+    ) -> Result<(), std::fmt::Error>
+    where
+        K: Debug,
+        V: Debug,
+        ESI: ExactSizeIterator<Item = (K, V)>,
+        ESII: IntoIterator<IntoIter = ESI>;
+}
+
+impl<'b> FormatterTruncateExt for Formatter<'b> {
+    fn debug_list_truncated<D, ESI, ESII>(
+        &mut self,
+        entries: ESII,
+        max_length: usize,
+    ) -> Result<(), std::fmt::Error>
+    where
+        D: Debug,
+        ESI: ExactSizeIterator<Item = D>,
+        ESII: IntoIterator<IntoIter = ESI>,
+    {
+        format_debug_truncated_to_max_length(self, entries, max_length)
+    }
+
+    fn debug_map_truncated<K, V, ESI, ESII>(
+        &mut self,
+        entries: ESII,
+        max_length: usize,
+    ) -> Result<(), std::fmt::Error>
+    where
+        K: Debug,
+        V: Debug,
+        ESI: ExactSizeIterator<Item = (K, V)>,
+        ESII: IntoIterator<IntoIter = ESI>,
+    {
+        format_debug_map_truncated_to_max_length(self, entries, max_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_debug_builders_truncated {
+        use super::super::{DebugStructTruncateExt, FormatterTruncateExt};
+        use linked_hash_set::LinkedHashSet;
+        use std::collections::BTreeMap;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithCollections {
+            hash_set: LinkedHashSet<i32>,
+            map: BTreeMap<i32, i32>,
+            max_length: usize,
+        }
+
+        impl Debug for StructWithCollections {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                f.debug_struct("StructWithCollections")
+                    .field_truncated("hash_set", &self.hash_set, self.max_length)
+                    .field_truncated("map", &self.map, self.max_length)
+                    .finish()
+            }
+        }
+
+        struct ListOfCollections(StructWithCollections);
+
+        impl Debug for ListOfCollections {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                f.debug_list_truncated(&self.0.hash_set, self.0.max_length)
+            }
+        }
+
+        struct MapOfCollections(StructWithCollections);
+
+        impl Debug for MapOfCollections {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                f.debug_map_truncated(&self.0.map, self.0.max_length)
+            }
+        }
+
+        fn struct_with_collections(max_length: usize) -> StructWithCollections {
+            StructWithCollections {
+                hash_set: (1..=3).collect(),
+                map: (1..=3).map(|i| (i, -i)).collect(),
+                max_length,
+            }
+        }
+
+        #[test]
+        fn debug_struct_field_truncated() {
+            let result = format!("{:?}", struct_with_collections(2));
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 hash_set: (length=3 truncated to 2) {1, 2}..., \
+                 map: (length=3 truncated to 2) {1: -1, 2: -2}... }"
+            );
+        }
+
+        #[test]
+        fn debug_list_truncated() {
+            let result = format!("{:?}", ListOfCollections(struct_with_collections(2)));
+
+            assert_eq!(result, "(length=3 truncated to 2) [1, 2]...");
+        }
+
+        #[test]
+        fn debug_struct_field_truncated_ignores_precision() {
+            let result = format!("{:.1?}", struct_with_collections(2));
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 hash_set: (length=3 truncated to 2) {1, 2}..., \
+                 map: (length=3 truncated to 2) {1: -1, 2: -2}... }"
+            );
+        }
+
+        #[test]
+        fn debug_list_truncated_to_zero() {
+            let result = format!("{:?}", ListOfCollections(struct_with_collections(0)));
+
+            assert_eq!(result, "(length=3)");
+        }
+
+        #[test]
+        fn debug_list_truncated_matches_untruncated() {
+            let result = format!("{:?}", ListOfCollections(struct_with_collections(3)));
+
+            assert_eq!(result, "[1, 2, 3]");
+        }
+
+        #[test]
+        fn debug_map_truncated() {
+            let result = format!("{:?}", MapOfCollections(struct_with_collections(1)));
+
+            assert_eq!(result, "(length=3 truncated to 1) {1: -1}...");
+        }
+
+        #[test]
+        fn debug_map_truncated_to_zero() {
+            let result = format!("{:?}", MapOfCollections(struct_with_collections(0)));
+
+            assert_eq!(result, "(length=3)");
+        }
+
+        #[test]
+        fn debug_map_truncated_pretty() {
+            let result = format!("{:#?}", MapOfCollections(struct_with_collections(1)));
+
+            assert_eq!(
+                result,
+                "(length=3 truncated to 1) {\n    1: -1,\n    ...,\n}"
+            );
+        }
+    }
+}
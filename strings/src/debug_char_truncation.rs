@@ -0,0 +1,212 @@
+use std::fmt::{Debug, Formatter, Write};
+
+/// Written after output cut by `DebugMaxChars`.
+/// The number of elided chars is unknown since formatting stops at the cut.
+pub const ELIDED_CHARS_MARKER: &str = "…(+? chars elided)";
+
+/// `fmt::Write` adapter which passes the first `max_chars` chars on to `inner`, then fails,
+/// so whatever is being formatted stops rather than formatting output which is thrown away.
+/// Chars are never split, whatever the byte boundaries of the writes.
+pub struct TruncatingWriter<W> {
+    inner: W,
+    remaining_chars: usize,
+    truncated: bool,
+}
+
+impl<W: Write> TruncatingWriter<W> {
+    pub fn new(inner: W, max_chars: usize) -> Self {
+        Self {
+            inner,
+            remaining_chars: max_chars,
+            truncated: false,
+        }
+    }
+
+    /// Whether output was cut, in which case the error returned to the caller
+    /// came from this writer rather than from `inner`.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for TruncatingWriter<W> {
+    fn write_str(&mut self, s: &str) -> Result<(), std::fmt::Error> {
+        if self.truncated {
+            return Err(std::fmt::Error);
+        }
+
+        match s.char_indices().nth(self.remaining_chars) {
+            None => {
+                self.remaining_chars -= s.chars().count();
+                self.inner.write_str(s)
+            }
+            Some((index, _)) => {
+                self.remaining_chars = 0;
+                self.truncated = true;
+                self.inner.write_str(&s[..index])?;
+                Err(std::fmt::Error)
+            }
+        }
+    }
+}
+
+/// Caps the `Debug` output of any value to the given number of chars, or to the format precision if set,
+/// followed by `ELIDED_CHARS_MARKER` if cut.
+/// The value is formatted with `{:?}`, or `{:#?}` for alternate, so other flags do not reach it.
+/// ```rust
+/// use strings::debug_char_truncation::DebugMaxChars;
+/// let data = Some("a long string");
+/// assert_eq!(
+///     format!("{:?}", DebugMaxChars(data, 10)),
+///     "Some(\"a lo…(+? chars elided)"
+/// );
+/// assert_eq!(format!("{:?}", DebugMaxChars(data, 100)), "Some(\"a long string\")");
+/// ```
+pub struct DebugMaxChars<T>(pub T, pub usize);
+
+impl<T: Debug> Debug for DebugMaxChars<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let max_chars = f.precision().unwrap_or(self.1);
+        let mut writer = TruncatingWriter::new(&mut *f, max_chars);
+        let result = if writer.inner.alternate() {
+            write!(writer, "{:#?}", self.0)
+        } else {
+            write!(writer, "{:?}", self.0)
+        };
+
+        if writer.is_truncated() {
+            f.write_str(ELIDED_CHARS_MARKER)
+        } else {
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_truncating_writer {
+        use super::super::TruncatingWriter;
+        use std::fmt::Write;
+
+        fn truncate_string_for(inputs: &[&str], max_chars: usize) -> (String, bool) {
+            let mut writer = TruncatingWriter::new(String::new(), max_chars);
+            for input in inputs {
+                if writer.write_str(input).is_err() {
+                    break;
+                }
+            }
+            let truncated = writer.is_truncated();
+
+            (writer.into_inner(), truncated)
+        }
+
+        #[test]
+        fn truncating_writer_longer_than_max() {
+            let result = truncate_string_for(&["abc", "def"], 4);
+
+            assert_eq!(result, ("abcd".to_string(), true));
+        }
+
+        #[test]
+        fn truncating_writer_equal_max() {
+            let result = truncate_string_for(&["abc", "def"], 6);
+
+            assert_eq!(result, ("abcdef".to_string(), false));
+        }
+
+        #[test]
+        fn truncating_writer_never_splits_chars() {
+            let result = truncate_string_for(&["añ", "€😀x"], 4);
+
+            assert_eq!(result, ("añ€😀".to_string(), true));
+        }
+
+        #[test]
+        fn truncating_writer_fails_after_truncation() {
+            let mut writer = TruncatingWriter::new(String::new(), 1);
+
+            assert!(writer.write_str("ab").is_err());
+            assert!(writer.write_str("").is_err());
+            assert_eq!(writer.into_inner(), "a");
+        }
+    }
+
+    mod test_debug_max_chars {
+        use super::super::DebugMaxChars;
+        use std::cell::Cell;
+        use std::fmt::{Debug, Formatter};
+
+        /// Example item which counts how many times it was formatted.
+        struct CountedItem<'count> {
+            item: usize,
+            formatted: &'count Cell<usize>,
+        }
+
+        impl<'count> Debug for CountedItem<'count> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                self.formatted.set(self.formatted.get() + 1);
+                self.item.fmt(f)
+            }
+        }
+
+        fn truncate_debug_string_for<T: Debug>(input: T, max_chars: usize) -> String {
+            let result = format!("{:?}", DebugMaxChars(input, max_chars));
+
+            result
+        }
+
+        #[test]
+        fn debug_max_chars_longer_than_max() {
+            let result = truncate_debug_string_for(vec![1, 2, 3], 5);
+
+            assert_eq!(result, "[1, 2…(+? chars elided)");
+        }
+
+        #[test]
+        fn debug_max_chars_equal_max() {
+            let result = truncate_debug_string_for(vec![1, 2, 3], 9);
+
+            assert_eq!(result, "[1, 2, 3]");
+        }
+
+        #[test]
+        fn debug_max_chars_never_splits_chars() {
+            let result = truncate_debug_string_for("héllo", 3);
+
+            assert_eq!(result, "\"hé…(+? chars elided)");
+        }
+
+        #[test]
+        fn debug_max_chars_stops_formatting() {
+            let formatted = Cell::new(0);
+            let items = (0..1000)
+                .map(|item| CountedItem {
+                    item,
+                    formatted: &formatted,
+                })
+                .collect::<Vec<_>>();
+            let result = truncate_debug_string_for(items, 5);
+
+            assert_eq!(result, "[0, 1…(+? chars elided)");
+            assert!(formatted.get() < 10, "{}", formatted.get());
+        }
+
+        #[test]
+        fn debug_max_chars_pretty() {
+            let result = format!("{:#?}", DebugMaxChars(vec![1, 2], 8));
+
+            assert_eq!(result, "[\n    1,…(+? chars elided)");
+        }
+
+        #[test]
+        fn debug_max_chars_precision_overrides_max_chars() {
+            let result = format!("{:.2?}", DebugMaxChars(vec![1, 2], 100));
+
+            assert_eq!(result, "[1…(+? chars elided)");
+        }
+    }
+}
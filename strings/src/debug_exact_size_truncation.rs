@@ -1,98 +1,44 @@
-use std::fmt::{Debug, Formatter};
-use std::hash::Hash;
-
-/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter`,
-/// truncating to the specified length.
-pub fn format_debug_exact_size_truncated_to_max_length<D, ESI, ESII>(
-    f: &mut Formatter<'_>,
-    exact_into_iter: ESII,
-    max_length: usize,
-) -> Result<(), std::fmt::Error>
-where
-    D: Debug + Eq + Hash,
-    ESI: ExactSizeIterator<Item = D>,
-    ESII: IntoIterator<IntoIter = ESI>,
-{
-    let exact_iter = exact_into_iter.into_iter();
-    let debug_exact_iter_length = exact_iter.len();
-
-    if max_length == 0 {
-        if debug_exact_iter_length == 0 {
-            f.debug_list().entries(exact_iter).finish()
-        } else {
-            write!(f, "(length={})", debug_exact_iter_length)
-        }
-    } else if debug_exact_iter_length <= max_length {
-        f.debug_list().entries(exact_iter).finish()
-    } else {
-        write!(
-            f,
-            "(length={} truncated to {}) ",
-            debug_exact_iter_length, max_length
-        )?;
-        f.debug_list()
-            .entries(exact_iter.into_iter().take(max_length))
-            .finish()?;
-        f.write_str("...")
-    }
-}
-
-trait TruncateDebug<D: Debug + Eq + Hash, const SIZE: usize> {
-    const MAX_LENGTH: usize = SIZE;
-
-    fn format_debug_truncated_to_max_length(
-        &self,
-        f: &mut Formatter<'_>,
-    ) -> Result<(), std::fmt::Error>;
-
-    /// Helper function for `IntoIterator < IntoIter=Iterator< Item=Debug > >` fixing the length.
-    fn format_debug_exact_size_truncated_to_max_length<ESI, ESII>(
-        f: &mut Formatter<'_>,
-        exact_into_iter: ESII,
-    ) -> Result<(), std::fmt::Error>
-    where
-        ESI: ExactSizeIterator<Item = D>,
-        ESII: IntoIterator<IntoIter = ESI>,
-    {
-        format_debug_exact_size_truncated_to_max_length(f, exact_into_iter, Self::MAX_LENGTH)
-    }
-}
-
-pub struct ExactSizeIntoIterHolder<ESII, const SIZE: usize> {
-    exact_size_into_iter: ESII,
-}
-
-impl<'esii, ESII, ESI, D, const SIZE: usize> TruncateDebug<D, SIZE>
-    for ExactSizeIntoIterHolder<&'esii ESII, SIZE>
-where
-    D: Debug + Eq + Hash,
-    ESI: ExactSizeIterator<Item = D>,
-    &'esii ESII: IntoIterator<IntoIter = ESI>,
-{
-    fn format_debug_truncated_to_max_length(
-        &self,
-        f: &mut Formatter<'_>,
-    ) -> Result<(), std::fmt::Error> {
-        Self::format_debug_exact_size_truncated_to_max_length(f, self.exact_size_into_iter)
-    }
-}
-
-impl<'esii, ESII, ESI, D, const SIZE: usize> Debug for ExactSizeIntoIterHolder<&'esii ESII, SIZE>
-where
-    D: Debug + Eq + Hash,
-    ESI: ExactSizeIterator<Item = D>,
-    &'esii ESII: IntoIterator<IntoIter = ESI>,
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        self.format_debug_truncated_to_max_length(f)
-    }
-}
+pub use crate::debug_run_length_truncation::{
+    format_debug_run_length_truncated_to_max_length as format_debug_exact_size_run_length_truncated_to_max_length,
+    format_debug_run_length_truncated_with_options as format_debug_exact_size_run_length_truncated_with_options,
+};
+pub use crate::debug_sampled_truncation::{
+    format_debug_sampled_by_reservoir as format_debug_exact_size_sampled_by_reservoir,
+    format_debug_sampled_by_stride as format_debug_exact_size_sampled_by_stride,
+};
+pub use crate::debug_truncation::{
+    format_debug_map_truncated_to_max_length as format_debug_map_exact_size_truncated_to_max_length,
+    format_debug_map_truncated_with_options as format_debug_map_exact_size_truncated_with_options,
+    format_debug_shaped_truncated_to_max_length as format_debug_exact_size_shaped_truncated_to_max_length,
+    format_debug_shaped_truncated_with_options as format_debug_exact_size_shaped_truncated_with_options,
+    format_debug_truncated_to_head_and_buffered_tail as format_debug_exact_size_truncated_to_head_and_buffered_tail,
+    format_debug_truncated_to_head_and_tail as format_debug_exact_size_truncated_to_head_and_tail,
+    format_debug_truncated_to_max_chars as format_debug_exact_size_truncated_to_max_chars,
+    format_debug_truncated_to_max_length as format_debug_exact_size_truncated_to_max_length,
+    format_debug_truncated_to_max_length_and_element_chars as format_debug_exact_size_truncated_to_max_length_and_element_chars,
+    format_debug_truncated_with_options as format_debug_exact_size_truncated_with_options,
+    ContainerShape,
+};
+use crate::debug_truncation::{
+    TruncatedDebug, TruncatedMapDebug, TruncatedOnceDebug, DEFAULT_DEBUG_FMT_MAX_ITEMS,
+};
+
+/// Compatibility name for `TruncatedDebug` over an `IntoIterator<IntoIter=ExactSizeIterator>>`.
+pub type ExactSizeIntoIterHolder<ESII, const SIZE: usize> = TruncatedDebug<ESII, SIZE>;
+
+/// Compatibility name for `TruncatedOnceDebug` over an `IntoIterator<IntoIter=ExactSizeIterator>>`
+/// which is consumed the first time it is formatted.
+pub type ExactSizeIntoIterOnceHolder<ESII, const SIZE: usize> = TruncatedOnceDebug<ESII, SIZE>;
+
+/// Compatibility name for `TruncatedMapDebug` over an `IntoIterator<IntoIter=ExactSizeIterator<Item=(K, V)>>>`.
+pub type ExactSizeMapIntoIterHolder<ESII, const SIZE: usize> = TruncatedMapDebug<ESII, SIZE>;
 
 /// Arbitrary constant used to truncate number of items in debug string.
-pub const DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS: usize = 42;
+pub const DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS: usize = DEFAULT_DEBUG_FMT_MAX_ITEMS;
 
 /// Truncates `IntoIterator` item length in debug string where underlying iterator is an `ExactSizeIterator`
-/// to maximum of `DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS`.
+/// to maximum of `DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS`, or to the format precision if set.
+/// Note the precision is also passed on to the items, so affects e.g. `f64` items.
 /// Example which doesn't truncate:
 /// ```rust
 /// use strings::debug_exact_size_truncation::truncated_exact_size_debug_fmt;
@@ -108,12 +54,49 @@ pub const DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS: usize = 42;
 /// assert!(debug_string.starts_with("result: (length=43 truncated to 42) [1, 2, 3"));
 /// assert!(debug_string.ends_with("..."));
 /// ```
+/// Example which truncates to the format precision:
+/// ```rust
+/// use strings::debug_exact_size_truncation::truncated_exact_size_debug_fmt;
+/// let data = [1, 2, 3, 4];
+/// let debug_string = format!("result: {:.2?}", truncated_exact_size_debug_fmt(&data));
+/// assert_eq!(debug_string, "result: (length=4 truncated to 2) [1, 2]...");
+/// ```
 pub fn truncated_exact_size_debug_fmt<ESII>(
     exact_size_into_iter: ESII,
 ) -> ExactSizeIntoIterHolder<ESII, DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS> {
-    ExactSizeIntoIterHolder {
-        exact_size_into_iter,
-    }
+    ExactSizeIntoIterHolder::new(exact_size_into_iter)
+}
+
+/// Truncates `IntoIterator` item length in debug string where underlying iterator is an `ExactSizeIterator`
+/// to maximum of `DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS`, or to the format precision if set,
+/// consuming it the first time it is formatted, so it need not be `Clone`.
+/// ```rust
+/// use strings::debug_exact_size_truncation::truncated_exact_size_debug_fmt_once;
+/// let data = vec![vec![1], vec![2, 3]];
+/// let debug = truncated_exact_size_debug_fmt_once(data.into_iter().map(|v| v.len()));
+/// assert_eq!(format!("result: {:?}", debug), "result: [1, 2]");
+/// assert_eq!(format!("result: {:?}", debug), "result: <already formatted>");
+/// ```
+pub fn truncated_exact_size_debug_fmt_once<ESII>(
+    exact_size_into_iter: ESII,
+) -> ExactSizeIntoIterOnceHolder<ESII, DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS> {
+    ExactSizeIntoIterOnceHolder::new(exact_size_into_iter)
+}
+
+/// Truncates map-like `IntoIterator` entry length in debug string where underlying iterator is an `ExactSizeIterator`
+/// of key/value pairs to maximum of `DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS`, or to the format precision if set.
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use strings::debug_exact_size_truncation::truncated_exact_size_map_debug_fmt;
+/// let data = (1..=50).map(|i| (i, i * i)).collect::<BTreeMap<_, _>>();
+/// let debug_string = format!("result: {:?}", truncated_exact_size_map_debug_fmt(&data));
+/// assert!(debug_string.starts_with("result: (length=50 truncated to 42) {1: 1, 2: 4, 3: 9"));
+/// assert!(debug_string.ends_with("42: 1764}..."));
+/// ```
+pub fn truncated_exact_size_map_debug_fmt<ESII>(
+    exact_size_into_iter: ESII,
+) -> ExactSizeMapIntoIterHolder<ESII, DEFAULT_EXACT_SIZE_DEBUG_FMT_MAX_ITEMS> {
+    ExactSizeMapIntoIterHolder::new(exact_size_into_iter)
 }
 
 #[cfg(test)]
@@ -131,9 +114,7 @@ mod tests {
         fn test_truncated_debug<ESII>(
             exact_size_into_iter: ESII,
         ) -> ExactSizeIntoIterHolder<ESII, MAX_TEST_ITEMS> {
-            ExactSizeIntoIterHolder::<ESII, MAX_TEST_ITEMS> {
-                exact_size_into_iter,
-            }
+            ExactSizeIntoIterHolder::<ESII, MAX_TEST_ITEMS>::new(exact_size_into_iter)
         }
 
         /// Example struct which has a LinkedHashSet field and formats using `test_truncated_debug()`.
@@ -189,6 +170,22 @@ mod tests {
 
             assert_eq!(result, "test_hash_set: [1, 2, 3, 4]");
         }
+
+        #[test]
+        fn truncated_exact_size_length_precision_overrides_max_length() {
+            let hash_set = [1, 2, 3, 4].iter().cloned().collect::<LinkedHashSet<_>>();
+            let result = format!("{:.2?}", test_truncated_debug(&hash_set));
+
+            assert_eq!(result, "(length=4 truncated to 2) [1, 2]...");
+        }
+
+        #[test]
+        fn truncated_exact_size_length_precision_zero() {
+            let hash_set = [1, 2, 3, 4].iter().cloned().collect::<LinkedHashSet<_>>();
+            let result = format!("{:.0?}", test_truncated_debug(&hash_set));
+
+            assert_eq!(result, "(length=4)");
+        }
     }
 
     mod test_debug_exact_size_iterator_truncated_to_max_length {
@@ -260,4 +257,351 @@ mod tests {
             assert_eq!(result, "(length=5)");
         }
     }
+
+    mod test_debug_exact_size_iterator_truncated_to_head_and_tail {
+        use super::super::{
+            format_debug_exact_size_truncated_to_head_and_buffered_tail,
+            format_debug_exact_size_truncated_to_head_and_tail,
+        };
+        use linked_hash_set::LinkedHashSet;
+        use std::fmt::{Debug, Formatter};
+        use std::hash::Hash;
+
+        /// `Vec` formats using the `DoubleEndedIterator` path.
+        struct StructWithAVec<D> {
+            vec: Vec<D>,
+            head_length: usize,
+            tail_length: usize,
+        }
+
+        impl<D: Debug> Debug for StructWithAVec<D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_exact_size_truncated_to_head_and_tail(
+                    f,
+                    &self.vec,
+                    self.head_length,
+                    self.tail_length,
+                )
+            }
+        }
+
+        /// `LinkedHashSet` iterators are not `DoubleEndedIterator` so format using the buffered path.
+        struct StructWithAHashSet<D> {
+            hash_set: LinkedHashSet<D>,
+            head_length: usize,
+            tail_length: usize,
+        }
+
+        impl<D: Debug + Hash + Eq> Debug for StructWithAHashSet<D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_exact_size_truncated_to_head_and_buffered_tail(
+                    f,
+                    &self.hash_set,
+                    self.head_length,
+                    self.tail_length,
+                )
+            }
+        }
+
+        /// Format `inputs` using both paths, checking they agree.
+        fn truncate_debug_string_for(
+            inputs: &[i32],
+            head_length: usize,
+            tail_length: usize,
+        ) -> String {
+            let vec_result = format!(
+                "{:?}",
+                StructWithAVec {
+                    vec: inputs.to_vec(),
+                    head_length,
+                    tail_length,
+                }
+            );
+            let hash_set_result = format!(
+                "{:?}",
+                StructWithAHashSet {
+                    hash_set: inputs.iter().cloned().collect::<LinkedHashSet<_>>(),
+                    head_length,
+                    tail_length,
+                }
+            );
+            assert_eq!(vec_result, hash_set_result);
+
+            vec_result
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_to_head_and_tail_longer_than_max() {
+            let inputs = (1..=1000).collect::<Vec<_>>();
+            let result = truncate_debug_string_for(&inputs, 5, 3);
+
+            assert_eq!(
+                result,
+                "(length=1000 showing 5+3) [1, 2, 3, 4, 5, ..., 998, 999, 1000]"
+            );
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_to_head_only() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 2, 0);
+
+            assert_eq!(result, "(length=5 showing 2+0) [1, 2, ...]");
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_to_tail_only() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 0, 2);
+
+            assert_eq!(result, "(length=5 showing 0+2) [..., 4, 5]");
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_to_head_and_tail_equal_max() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 3, 2);
+
+            assert_eq!(result, "[1, 2, 3, 4, 5]");
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_to_head_and_tail_zero() {
+            let result = truncate_debug_string_for(&[], 0, 0);
+
+            assert_eq!(result, "[]");
+        }
+
+        #[test]
+        fn empty_debug_exact_size_iterator_truncated_to_head_and_tail_zero() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 0, 0);
+
+            assert_eq!(result, "(length=5)");
+        }
+    }
+
+    mod test_debug_map_exact_size_iterator_truncated_to_max_length {
+        use super::super::format_debug_map_exact_size_truncated_to_max_length;
+        use std::collections::BTreeMap;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithABTreeMap<K, V> {
+            map: BTreeMap<K, V>,
+            max_length: usize,
+        }
+
+        impl<K, V> StructWithABTreeMap<K, V> {
+            fn new(map: BTreeMap<K, V>, max_length: usize) -> Self {
+                Self { map, max_length }
+            }
+        }
+
+        impl<K: Debug, V: Debug> Debug for StructWithABTreeMap<K, V> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_map_exact_size_truncated_to_max_length(f, &self.map, self.max_length)
+            }
+        }
+
+        fn truncate_debug_string_for(inputs: &[i32], max_length: usize) -> String {
+            let map = inputs
+                .iter()
+                .map(|&input| (input, format!("v{}", input)))
+                .collect::<BTreeMap<_, _>>();
+            let input = StructWithABTreeMap::new(map, max_length);
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn debug_map_exact_size_iterator_truncated_to_max_length_longer_than_max() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 4);
+
+            assert_eq!(
+                result,
+                r#"(length=5 truncated to 4) {1: "v1", 2: "v2", 3: "v3", 4: "v4"}..."#
+            );
+        }
+
+        #[test]
+        fn debug_map_exact_size_iterator_truncated_to_max_length_equal_max() {
+            let result = truncate_debug_string_for(&[1, 2], 2);
+
+            assert_eq!(result, r#"{1: "v1", 2: "v2"}"#);
+        }
+
+        #[test]
+        fn debug_map_exact_size_iterator_truncated_to_max_length_zero() {
+            let result = truncate_debug_string_for(&[], 0);
+
+            assert_eq!(result, "{}");
+        }
+
+        #[test]
+        fn empty_debug_map_exact_size_iterator_truncated_to_max_length_zero() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 0);
+
+            assert_eq!(result, "(length=5)");
+        }
+    }
+
+    mod test_truncated_exact_size_map {
+        use super::super::ExactSizeMapIntoIterHolder;
+        use std::collections::HashMap;
+
+        const MAX_TEST_ITEMS: usize = 2;
+
+        fn test_truncated_map_debug<ESII>(
+            exact_size_into_iter: ESII,
+        ) -> ExactSizeMapIntoIterHolder<ESII, MAX_TEST_ITEMS> {
+            ExactSizeMapIntoIterHolder::<ESII, MAX_TEST_ITEMS>::new(exact_size_into_iter)
+        }
+
+        #[test]
+        fn truncated_exact_size_hash_map_input_length_greater_than_max_length() {
+            let map = (1..=3).map(|i| (i, i)).collect::<HashMap<_, _>>();
+            let result = format!("{:?}", test_truncated_map_debug(&map));
+
+            assert!(
+                result.starts_with("(length=3 truncated to 2) {"),
+                "{}",
+                result
+            );
+            assert!(result.ends_with("}..."), "{}", result);
+            assert_eq!(result.matches(": ").count(), 2, "{}", result);
+        }
+
+        #[test]
+        fn truncated_exact_size_hash_map_precision_overrides_max_length() {
+            let map = (1..=1).map(|i| (i, i)).collect::<HashMap<_, _>>();
+            let result = format!("{:.0?}", test_truncated_map_debug(&map));
+
+            assert_eq!(result, "(length=1)");
+        }
+
+        #[test]
+        fn truncated_exact_size_hash_map_input_length_equal_to_max_length() {
+            let map = (1..=1).map(|i| (i, i)).collect::<HashMap<_, _>>();
+            let result = format!("{:?}", test_truncated_map_debug(&map));
+
+            assert_eq!(result, "{1: 1}");
+        }
+    }
+
+    mod test_debug_exact_size_iterator_truncated_formatter_flags {
+        use super::super::{
+            format_debug_exact_size_truncated_to_head_and_buffered_tail,
+            format_debug_exact_size_truncated_to_max_length,
+            format_debug_map_exact_size_truncated_to_max_length,
+        };
+        use linked_hash_set::LinkedHashSet;
+        use std::collections::BTreeMap;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithAHashSet {
+            hash_set: LinkedHashSet<i32>,
+        }
+
+        impl Debug for StructWithAHashSet {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_exact_size_truncated_to_max_length(f, &self.hash_set, 2)
+            }
+        }
+
+        struct StructWithAHeadAndTailHashSet {
+            hash_set: LinkedHashSet<i32>,
+        }
+
+        impl Debug for StructWithAHeadAndTailHashSet {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_exact_size_truncated_to_head_and_buffered_tail(f, &self.hash_set, 1, 1)
+            }
+        }
+
+        struct StructWithABTreeMap {
+            map: BTreeMap<i32, Vec<i32>>,
+        }
+
+        impl Debug for StructWithABTreeMap {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_map_exact_size_truncated_to_max_length(f, &self.map, 1)
+            }
+        }
+
+        fn hash_set_of(inputs: &[i32]) -> LinkedHashSet<i32> {
+            inputs.iter().cloned().collect()
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_alternate() {
+            let input = StructWithAHashSet {
+                hash_set: hash_set_of(&[1, 2, 3]),
+            };
+            let result = format!("{:#?}", input);
+
+            assert_eq!(
+                result,
+                "(length=3 truncated to 2) [\n    1,\n    2,\n    ...,\n]"
+            );
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_lower_hex() {
+            let input = StructWithAHashSet {
+                hash_set: hash_set_of(&[10, 11, 12]),
+            };
+            let result = format!("{:x?}", input);
+
+            assert_eq!(result, "(length=3 truncated to 2) [a, b]...");
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_upper_hex() {
+            let input = StructWithAHashSet {
+                hash_set: hash_set_of(&[10, 11, 12]),
+            };
+            let result = format!("{:X?}", input);
+
+            assert_eq!(result, "(length=3 truncated to 2) [A, B]...");
+        }
+
+        #[test]
+        fn debug_exact_size_iterator_truncated_to_head_and_tail_upper_hex() {
+            let input = StructWithAHeadAndTailHashSet {
+                hash_set: hash_set_of(&[10, 11, 12]),
+            };
+            let result = format!("{:X?}", input);
+
+            assert_eq!(result, "(length=3 showing 1+1) [A, ..., C]");
+        }
+
+        #[test]
+        fn debug_map_exact_size_iterator_truncated_alternate() {
+            let input = StructWithABTreeMap {
+                map: vec![(1, vec![10]), (2, vec![20])].into_iter().collect(),
+            };
+            let result = format!("{:#?}", input);
+
+            assert_eq!(
+                result,
+                "(length=2 truncated to 1) {\n    1: [\n        10,\n    ],\n    ...,\n}"
+            );
+        }
+
+        #[test]
+        fn debug_map_exact_size_iterator_not_truncated_alternate() {
+            let map = vec![(1, vec![10])].into_iter().collect::<BTreeMap<_, _>>();
+            let expected = format!("{:#?}", map);
+            let result = format!("{:#?}", StructWithABTreeMap { map });
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn debug_map_exact_size_iterator_truncated_lower_hex() {
+            let input = StructWithABTreeMap {
+                map: vec![(10, vec![11]), (12, vec![13])].into_iter().collect(),
+            };
+            let result = format!("{:x?}", input);
+
+            assert_eq!(result, "(length=2 truncated to 1) {a: [b]}...");
+        }
+    }
 }
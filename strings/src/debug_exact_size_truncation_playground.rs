@@ -1,90 +1,12 @@
-use std::fmt::{Debug, Formatter};
-use std::hash::Hash;
-
-pub fn format_debug_exact_size_truncated_to_max_length<D, ESI, ESII>(
-    f: &mut Formatter<'_>,
-    exact_into_iter: ESII,
-    max_length: usize,
-) -> Result<(), std::fmt::Error>
-where
-    D: Debug + Eq + Hash,
-    ESI: ExactSizeIterator<Item = D>,
-    ESII: IntoIterator<IntoIter = ESI>,
-{
-    let exact_iter = exact_into_iter.into_iter();
-    let debug_slice_length = exact_iter.len();
-
-    if max_length == 0 {
-        if debug_slice_length == 0 {
-            f.debug_list().entries(exact_iter).finish()
-        } else {
-            write!(f, "(length={})", debug_slice_length)
-        }
-    } else if debug_slice_length <= max_length {
-        f.debug_list().entries(exact_iter).finish()
-    } else {
-        write!(
-            f,
-            "(length={} truncated to {}) ",
-            debug_slice_length, max_length
-        )?;
-        f.debug_list()
-            .entries(exact_iter.into_iter().take(max_length))
-            .finish()?;
-        f.write_str("...")
-    }
-}
-
-trait TruncateDebug<D: Debug + Eq + Hash> {
-    const MAX_LENGTH: usize;
+pub use crate::debug_truncation::format_debug_truncated_to_max_length as format_debug_exact_size_truncated_to_max_length;
+use crate::debug_truncation::{TruncatedDebug, DEFAULT_DEBUG_FMT_MAX_ITEMS};
 
-    fn format_debug_truncated_to_max_length(
-        &self,
-        f: &mut Formatter<'_>,
-    ) -> Result<(), std::fmt::Error>;
-}
-
-pub struct ExactSizeIntoIterHolder<ESII> {
-    exact_size_into_iter: ESII,
-}
+pub type ExactSizeIntoIterHolder<ESII> = TruncatedDebug<ESII, DEFAULT_DEBUG_FMT_MAX_ITEMS>;
 
 pub fn truncated_exact_size_debug_fmt<ESII>(
     exact_size_into_iter: ESII,
 ) -> ExactSizeIntoIterHolder<ESII> {
-    ExactSizeIntoIterHolder {
-        exact_size_into_iter,
-    }
-}
-
-impl<'esii, ESII, ESI, D> TruncateDebug<D> for ExactSizeIntoIterHolder<&'esii ESII>
-where
-    D: Debug + Eq + Hash,
-    ESI: ExactSizeIterator<Item = D>,
-    &'esii ESII: IntoIterator<IntoIter = ESI>,
-{
-    const MAX_LENGTH: usize = 42;
-
-    fn format_debug_truncated_to_max_length(
-        &self,
-        f: &mut Formatter<'_>,
-    ) -> Result<(), std::fmt::Error> {
-        format_debug_exact_size_truncated_to_max_length(
-            f,
-            self.exact_size_into_iter,
-            Self::MAX_LENGTH,
-        )
-    }
-}
-
-impl<'esii, ESII, ESI, D> Debug for ExactSizeIntoIterHolder<&'esii ESII>
-where
-    D: Debug + Eq + Hash,
-    ESI: ExactSizeIterator<Item = D>,
-    &'esii ESII: IntoIterator<IntoIter = ESI>,
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        self.format_debug_truncated_to_max_length(f)
-    }
+    ExactSizeIntoIterHolder::new(exact_size_into_iter)
 }
 
 #[cfg(test)]
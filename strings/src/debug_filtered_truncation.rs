@@ -0,0 +1,410 @@
+use crate::debug_truncation::{CappedElement, ContainerShape, WithinCharBudget};
+use crate::debug_truncation_options::{ReportedLength, TruncationOptions, ZeroLengthDisplay};
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+
+/// Shows up to `max_length` items of `source` matching `predicate`, or as many as the format
+/// precision if set, e.g. `(length=5000, 37 matching, 10 shown) [..]...`.
+/// With no item to show only the counts are written, e.g. `(length=5000, 37 matching)`,
+/// whether none matched or none may be shown, unless `ZeroLengthDisplay` says otherwise,
+/// and when every item matches and is shown the output is the same as the untruncated `Debug`.
+/// A tail shows the last matching items, and `max_total_chars` limits the matching items shown
+/// as it does for other collections, while sampling does not apply and is ignored.
+/// Each time it is formatted `source` is iterated once, keeping at most as many items as are shown.
+pub struct FilteredTruncatedDebug<S, P> {
+    source: S,
+    predicate: P,
+    options: TruncationOptions,
+}
+
+impl<S, D, P> Debug for FilteredTruncatedDebug<S, P>
+where
+    S: IntoIterator<Item = D> + Clone,
+    D: Debug,
+    P: Fn(&D) -> bool,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        let tail_length = if options.shows_tail() {
+            options.tail_length
+        } else {
+            0
+        };
+        let mut length = 0;
+        let mut matching_length = 0;
+        let mut head = Vec::new();
+        let mut tail = VecDeque::with_capacity(tail_length);
+        for item in self.source.clone() {
+            length += 1;
+            if !(self.predicate)(&item) {
+                continue;
+            }
+            matching_length += 1;
+            if head.len() < options.head_length {
+                head.push(item);
+            } else if tail_length > 0 {
+                if tail.len() == tail_length {
+                    tail.pop_front();
+                }
+                tail.push_back(item);
+            }
+        }
+
+        let truncated = matching_length > head.len() + tail.len();
+        let shows_tail = truncated && !tail.is_empty();
+        let mut head = head
+            .into_iter()
+            .map(|item| CappedElement::new(item, &options))
+            .collect::<Vec<_>>();
+        let mut tail = tail
+            .into_iter()
+            .map(|item| CappedElement::new(item, &options))
+            .collect::<Vec<_>>();
+        if !shows_tail {
+            head.append(&mut tail);
+        }
+
+        if let Some(max_total_chars) = options.max_total_chars {
+            let mut items = WithinCharBudget::take_head(
+                f,
+                &mut head.into_iter(),
+                usize::MAX,
+                max_total_chars,
+                options.split_last_element,
+            )?;
+            if shows_tail {
+                items.take_tail(f, tail)?;
+            }
+            let truncated = truncated || items.budget_reached;
+            if !format_filtered_header(
+                f,
+                &options,
+                length,
+                matching_length,
+                items.shown_length(),
+                truncated,
+            )? {
+                return Ok(());
+            }
+            return items.format_entries(f, truncated, ContainerShape::List, &options.marker);
+        }
+
+        let head_length = head.len();
+        if !format_filtered_header(
+            f,
+            &options,
+            length,
+            matching_length,
+            head_length + tail.len(),
+            truncated,
+        )? {
+            return Ok(());
+        }
+        let entries = head.into_iter().chain(tail);
+        if !truncated {
+            ContainerShape::List.format_debug_entries(f, entries)
+        } else if shows_tail {
+            ContainerShape::List.format_debug_head_and_tail_entries(
+                f,
+                entries,
+                head_length,
+                0,
+                &options.marker,
+            )
+        } else {
+            ContainerShape::List.format_debug_truncated_entries(f, entries, &options.marker)
+        }
+    }
+}
+
+/// Write what comes before the matching items shown: nothing when every item matches and is
+/// shown, only the counts or the marker when none is shown, if `ZeroLengthDisplay` says so,
+/// and otherwise the filtered header. Returns whether the items should be written.
+fn format_filtered_header(
+    f: &mut Formatter<'_>,
+    options: &TruncationOptions,
+    length: usize,
+    matching_length: usize,
+    shown_length: usize,
+    truncated: bool,
+) -> Result<bool, std::fmt::Error> {
+    if matching_length == length && !truncated {
+        return Ok(true);
+    }
+    let reported_length = ReportedLength::Exact(length);
+    if shown_length == 0 {
+        match options.zero_length {
+            ZeroLengthDisplay::Length => {
+                write!(
+                    f,
+                    "(length{}, {} matching)",
+                    reported_length, matching_length
+                )?;
+                return Ok(false);
+            }
+            ZeroLengthDisplay::Marker if truncated => {
+                f.write_str(&options.marker)?;
+                return Ok(false);
+            }
+            _ => {}
+        }
+    }
+    options.format_filtered_header(f, reported_length, matching_length, shown_length)?;
+    Ok(true)
+}
+
+/// Shows up to `max_length` items of `source` for which `predicate` is `true`,
+/// reporting how many there were and how many matched.
+/// ```rust
+/// use strings::debug_filtered_truncation::filtered_truncated_debug;
+/// let results = (1..=5000)
+///     .map(|i| if i % 100 == 0 { Err(i) } else { Ok(i) })
+///     .collect::<Vec<Result<i32, i32>>>();
+/// assert_eq!(
+///     format!("{:?}", filtered_truncated_debug(&results, |result| result.is_err(), 2)),
+///     "(length=5000, 50 matching, 2 shown) [Err(100), Err(200)]..."
+/// );
+/// ```
+pub fn filtered_truncated_debug<S, D, P>(
+    source: S,
+    predicate: P,
+    max_length: usize,
+) -> FilteredTruncatedDebug<S, P>
+where
+    S: IntoIterator<Item = D> + Clone,
+    P: Fn(&D) -> bool,
+{
+    filtered_truncated_debug_with_options(
+        source,
+        predicate,
+        TruncationOptions::new().max_length(max_length),
+    )
+}
+
+/// Shows the items of `source` for which `predicate` is `true`, as many as `options` shows,
+/// with its marker, header, zero length and element limits.
+pub fn filtered_truncated_debug_with_options<S, D, P>(
+    source: S,
+    predicate: P,
+    options: TruncationOptions,
+) -> FilteredTruncatedDebug<S, P>
+where
+    S: IntoIterator<Item = D> + Clone,
+    P: Fn(&D) -> bool,
+{
+    FilteredTruncatedDebug {
+        source,
+        predicate,
+        options,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_filtered_truncated_debug {
+        use super::super::{filtered_truncated_debug, filtered_truncated_debug_with_options};
+        use crate::debug_truncation_options::{Sampling, TruncationOptions, ZeroLengthDisplay};
+        use std::fmt::{Debug, Formatter};
+
+        /// Example struct which shows only its failed results.
+        struct StructWithResults {
+            results: Vec<Result<i32, &'static str>>,
+            max_length: usize,
+        }
+
+        impl Debug for StructWithResults {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                f.debug_struct("StructWithResults")
+                    .field(
+                        "errors",
+                        &filtered_truncated_debug(
+                            &self.results,
+                            |result| result.is_err(),
+                            self.max_length,
+                        ),
+                    )
+                    .finish()
+            }
+        }
+
+        fn truncate_debug_string_for(
+            results: Vec<Result<i32, &'static str>>,
+            max_length: usize,
+        ) -> String {
+            let input = StructWithResults {
+                results,
+                max_length,
+            };
+            format!("{:?}", input)
+        }
+
+        #[test]
+        fn filtered_truncated_more_matching_than_max_length() {
+            let result =
+                truncate_debug_string_for(vec![Err("a"), Ok(1), Err("b"), Ok(2), Err("c")], 2);
+
+            assert_eq!(
+                result,
+                "StructWithResults { errors: \
+                 (length=5, 3 matching, 2 shown) [Err(\"a\"), Err(\"b\")]... }"
+            );
+        }
+
+        #[test]
+        fn filtered_truncated_all_matching_shown() {
+            let result = truncate_debug_string_for(vec![Ok(1), Err("a")], 2);
+
+            assert_eq!(
+                result,
+                "StructWithResults { errors: (length=2, 1 matching, 1 shown) [Err(\"a\")] }"
+            );
+        }
+
+        #[test]
+        fn filtered_truncated_none_matching() {
+            let result = truncate_debug_string_for(vec![Ok(1), Ok(2)], 2);
+
+            assert_eq!(
+                result,
+                "StructWithResults { errors: (length=2, 0 matching) }"
+            );
+        }
+
+        #[test]
+        fn filtered_truncated_every_item_matching_and_shown() {
+            let result = truncate_debug_string_for(vec![Err("a"), Err("b")], 2);
+
+            assert_eq!(
+                result,
+                "StructWithResults { errors: [Err(\"a\"), Err(\"b\")] }"
+            );
+        }
+
+        #[test]
+        fn filtered_truncated_zero_max_length() {
+            let result = truncate_debug_string_for(vec![Err("a"), Ok(1)], 0);
+
+            assert_eq!(
+                result,
+                "StructWithResults { errors: (length=2, 1 matching) }"
+            );
+        }
+
+        #[test]
+        fn filtered_truncated_with_options() {
+            let options = TruncationOptions::new()
+                .max_length(1)
+                .show_header(false)
+                .marker("~");
+            let result = format!(
+                "{:?}",
+                filtered_truncated_debug_with_options(1..=10, |i| i % 2 == 0, options)
+            );
+
+            assert_eq!(result, "[2]~");
+        }
+
+        #[test]
+        fn filtered_truncated_none_matching_empty_container() {
+            let options = TruncationOptions::new()
+                .max_length(2)
+                .zero_length(ZeroLengthDisplay::EmptyContainer);
+            let result = format!(
+                "{:?}",
+                filtered_truncated_debug_with_options(1..=3, |i| *i > 5, options)
+            );
+
+            assert_eq!(result, "(length=3, 0 matching, 0 shown) []");
+        }
+
+        #[test]
+        fn filtered_truncated_precision_overrides_max_length() {
+            let result = format!(
+                "{:.1?}",
+                filtered_truncated_debug(1..=10, |i| i % 2 == 0, 5)
+            );
+
+            assert_eq!(result, "(length=10, 5 matching, 1 shown) [2]...");
+        }
+
+        #[test]
+        fn filtered_truncated_head_and_tail() {
+            let options = TruncationOptions::new().head_and_tail(1, 2);
+            let result = format!(
+                "{:?}",
+                filtered_truncated_debug_with_options(1..=20, |i| i % 3 == 0, options)
+            );
+
+            assert_eq!(result, "(length=20, 6 matching, 3 shown) [3, ..., 15, 18]");
+        }
+
+        #[test]
+        fn filtered_truncated_head_and_tail_all_matching_shown() {
+            let options = TruncationOptions::new().head_and_tail(1, 2);
+            let result = format!(
+                "{:?}",
+                filtered_truncated_debug_with_options(1..=10, |i| i % 4 == 0, options)
+            );
+
+            assert_eq!(result, "(length=10, 2 matching, 2 shown) [4, 8]");
+        }
+
+        #[test]
+        fn filtered_truncated_precision_keeps_tail() {
+            let options = TruncationOptions::new().head_and_tail(3, 1);
+            let result = format!(
+                "{:.2?}",
+                filtered_truncated_debug_with_options(1..=10, |i| i % 2 == 0, options)
+            );
+
+            assert_eq!(result, "(length=10, 5 matching, 2 shown) [2, ..., 10]");
+        }
+
+        #[test]
+        fn filtered_truncated_max_total_chars() {
+            let options = TruncationOptions::new().max_length(5).max_total_chars(5);
+            let result = format!(
+                "{:?}",
+                filtered_truncated_debug_with_options(1..=40, |i| i % 10 == 0, options)
+            );
+
+            assert_eq!(result, "(length=40, 4 matching, 2 shown) [10, 20]...");
+        }
+
+        #[test]
+        fn filtered_truncated_max_total_chars_with_tail() {
+            let options = TruncationOptions::new()
+                .head_and_tail(2, 1)
+                .max_total_chars(6);
+            let result = format!(
+                "{:?}",
+                filtered_truncated_debug_with_options(1..=50, |i| i % 10 == 0, options)
+            );
+
+            assert_eq!(result, "(length=50, 5 matching, 3 shown) [10, 20, ..., 50]");
+        }
+
+        #[test]
+        fn filtered_truncated_ignores_sampling() {
+            let options = TruncationOptions::new()
+                .max_length(2)
+                .sampling(Sampling::Stride);
+            let result = format!(
+                "{:?}",
+                filtered_truncated_debug_with_options(1..=10, |i| i % 2 == 0, options)
+            );
+
+            assert_eq!(result, "(length=10, 5 matching, 2 shown) [2, 4]...");
+        }
+
+        #[test]
+        fn filtered_truncated_pretty() {
+            let result = format!("{:#?}", filtered_truncated_debug(1..=10, |i| *i > 8, 1));
+
+            assert_eq!(
+                result,
+                "(length=10, 2 matching, 1 shown) [\n    9,\n    ...,\n]"
+            );
+        }
+    }
+}
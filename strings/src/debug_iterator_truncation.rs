@@ -0,0 +1,653 @@
+use crate::debug_truncation::{CappedElement, ContainerShape, WithinCharBudget};
+use crate::debug_truncation_options::{ReportedLength, TruncationOptions, ZeroLengthDisplay};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+
+/// How the items left over after the first `max_length` are reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemainingItems {
+    /// Drain the rest of the iterator to report the exact length, e.g. `(length=100 truncated to 5)`.
+    Count,
+    /// Report a lower bound on the length using `Iterator::size_hint`, e.g. `(length≥6 truncated to 5)`.
+    SizeHint,
+    /// Peek a single item past the first `max_length`, reporting only `...and more`.
+    Peek,
+}
+
+/// Write an `IntoIterator` of `Debug` to `Formatter`, truncating to the specified length.
+/// Unlike `format_debug_exact_size_truncated_to_max_length` the length need not be known up front,
+/// and the iterator is only walked past `max_length + 1` items for `RemainingItems::Count`.
+pub fn format_debug_iter_truncated_to_max_length<D, I, II>(
+    f: &mut Formatter<'_>,
+    into_iter: II,
+    max_length: usize,
+    remaining_items: RemainingItems,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    I: Iterator<Item = D>,
+    II: IntoIterator<IntoIter = I>,
+{
+    let options = TruncationOptions::new().max_length(max_length);
+    format_debug_iter_truncated_with_options(f, into_iter, &options, remaining_items)
+}
+
+/// Write an `IntoIterator` of `Debug` to `Formatter`, truncating as specified by `options`.
+/// Showing a tail means walking the whole iterator, keeping the last items in a buffer,
+/// so the exact length is always reported in that case whatever `remaining_items` is.
+/// The tail is not shown for an iterator with no upper bound in its `Iterator::size_hint`,
+/// which may never end, so only the head is shown then, as with `options.max_length(head_length)`.
+/// `options.sampling` is not applied, as sampling needs the length up front,
+/// so the first items are shown as without it.
+pub fn format_debug_iter_truncated_with_options<D, I, II>(
+    f: &mut Formatter<'_>,
+    into_iter: II,
+    options: &TruncationOptions,
+    remaining_items: RemainingItems,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    I: Iterator<Item = D>,
+    II: IntoIterator<IntoIter = I>,
+{
+    let iter = into_iter.into_iter();
+    let options = match iter.size_hint() {
+        (_, None) if options.shows_tail() => {
+            Cow::Owned(options.clone().max_length(options.head_length))
+        }
+        _ => Cow::Borrowed(options),
+    };
+    let options = &*options;
+    let mut iter = iter.map(|element| CappedElement::new(element, options));
+    if let Some(max_total_chars) = options
+        .max_total_chars
+        .filter(|_| options.shown_length() > 0)
+    {
+        return format_debug_iter_within_char_budget(
+            f,
+            iter,
+            options,
+            max_total_chars,
+            remaining_items,
+        );
+    }
+
+    let head = iter.by_ref().take(options.head_length).collect::<Vec<_>>();
+
+    let next = match iter.next() {
+        Some(next) => next,
+        None => return f.debug_list().entries(head).finish(),
+    };
+
+    if options.shows_tail() {
+        let mut tail = VecDeque::new();
+        let mut length = head.len();
+        for item in std::iter::once(next).chain(iter) {
+            push_to_tail(&mut tail, item, options.tail_length);
+            length += 1;
+        }
+
+        return if length <= options.shown_length() {
+            f.debug_list().entries(head).entries(tail).finish()
+        } else {
+            options.format_header(f, ReportedLength::Exact(length))?;
+            ContainerShape::List.format_debug_head_and_tail_entries(
+                f,
+                head.into_iter().chain(tail),
+                options.head_length,
+                0,
+                &options.marker,
+            )
+        };
+    }
+
+    let (length, marker) = remaining_length(iter, head.len() + 1, remaining_items, options);
+
+    if options.head_length == 0 {
+        match (options.zero_length, length) {
+            (ZeroLengthDisplay::Length, Some(length)) => write!(f, "(length{})", length),
+            (ZeroLengthDisplay::Length, None) | (ZeroLengthDisplay::Marker, _) => {
+                f.write_str(&marker)
+            }
+            (ZeroLengthDisplay::EmptyContainer, _) => {
+                if let Some(length) = length {
+                    options.format_header(f, length)?;
+                }
+                ContainerShape::List.format_debug_truncated_entries(
+                    f,
+                    std::iter::empty::<()>(),
+                    &marker,
+                )
+            }
+        }
+    } else {
+        if let Some(length) = length {
+            options.format_header(f, length)?;
+        }
+        ContainerShape::List.format_debug_truncated_entries(f, head, &marker)
+    }
+}
+
+/// Write as many whole items of `iter` as fit within `max_total_chars` chars of item output,
+/// taking up to `options.head_length` from the front, then up to `options.tail_length` from the back.
+fn format_debug_iter_within_char_budget<D, I>(
+    f: &mut Formatter<'_>,
+    mut iter: I,
+    options: &TruncationOptions,
+    max_total_chars: usize,
+    remaining_items: RemainingItems,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    I: Iterator<Item = D>,
+{
+    let mut items = WithinCharBudget::take_head(
+        f,
+        &mut iter,
+        options.head_length,
+        max_total_chars,
+        options.split_last_element,
+    )?;
+
+    if options.shows_tail() {
+        let mut tail = VecDeque::new();
+        let mut length = items.taken_length;
+        for item in iter {
+            push_to_tail(&mut tail, item, options.tail_length);
+            length += 1;
+        }
+        let truncated = length > options.shown_length();
+        items.take_tail(f, tail.into())?;
+        return items.format(
+            f,
+            options,
+            Some(ReportedLength::Exact(length)),
+            truncated,
+            ContainerShape::List,
+            &options.marker,
+        );
+    }
+
+    let taken_length = if items.budget_reached {
+        items.taken_length
+    } else {
+        match iter.next() {
+            Some(_) => items.taken_length + 1,
+            None => {
+                return items.format(
+                    f,
+                    options,
+                    None,
+                    false,
+                    ContainerShape::List,
+                    &options.marker,
+                )
+            }
+        }
+    };
+    let (length, marker) = remaining_length(iter, taken_length, remaining_items, options);
+    items.format(f, options, length, true, ContainerShape::List, &marker)
+}
+
+/// Keep `item` among the last `tail_length` items seen.
+fn push_to_tail<D>(tail: &mut VecDeque<D>, item: D, tail_length: usize) {
+    if tail_length == 0 {
+        return;
+    }
+    if tail.len() == tail_length {
+        tail.pop_front();
+    }
+    tail.push_back(item);
+}
+
+/// The length to report, if any, for an iterator of which `taken_length` items were taken
+/// and `iter` is left, and the marker to write after the items shown.
+fn remaining_length<'options, I: Iterator>(
+    iter: I,
+    taken_length: usize,
+    remaining_items: RemainingItems,
+    options: &'options TruncationOptions,
+) -> (Option<ReportedLength>, Cow<'options, str>) {
+    match remaining_items {
+        RemainingItems::Count => (
+            Some(ReportedLength::Exact(taken_length + iter.count())),
+            Cow::Borrowed(&*options.marker),
+        ),
+        RemainingItems::SizeHint => {
+            let (lower_bound, _) = iter.size_hint();
+            (
+                Some(ReportedLength::AtLeast(taken_length + lower_bound)),
+                Cow::Borrowed(&*options.marker),
+            )
+        }
+        RemainingItems::Peek => (None, Cow::Owned(format!("{}and more", options.marker))),
+    }
+}
+
+/// Holds a cloneable `IntoIterator` (e.g. a reference to a collection, or a `Clone` iterator adapter)
+/// so it can be formatted every time `Debug::fmt` is called.
+/// Truncates to `SIZE` items, or to the format precision if set.
+pub struct IntoIterHolder<II, const SIZE: usize> {
+    into_iter: II,
+    options: TruncationOptions,
+    remaining_items: RemainingItems,
+}
+
+impl<II, const SIZE: usize> IntoIterHolder<II, SIZE> {
+    pub fn new(into_iter: II, remaining_items: RemainingItems) -> Self {
+        let options = TruncationOptions::new().max_length(SIZE);
+        Self::with_options(into_iter, options, remaining_items)
+    }
+
+    /// Truncate as specified by `options` rather than to `SIZE`.
+    pub fn with_options(
+        into_iter: II,
+        options: TruncationOptions,
+        remaining_items: RemainingItems,
+    ) -> Self {
+        Self {
+            into_iter,
+            options,
+            remaining_items,
+        }
+    }
+}
+
+impl<II, I, D, const SIZE: usize> Debug for IntoIterHolder<II, SIZE>
+where
+    D: Debug,
+    I: Iterator<Item = D>,
+    II: IntoIterator<IntoIter = I> + Clone,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        format_debug_iter_truncated_with_options(
+            f,
+            self.into_iter.clone(),
+            &options,
+            self.remaining_items,
+        )
+    }
+}
+
+/// Arbitrary constant used to truncate number of items in debug string,
+/// the same as for exact-size iterators.
+pub use crate::debug_truncation::DEFAULT_DEBUG_FMT_MAX_ITEMS as DEFAULT_ITER_DEBUG_FMT_MAX_ITEMS;
+
+/// Truncates `IntoIterator` item length in debug string to maximum of `DEFAULT_ITER_DEBUG_FMT_MAX_ITEMS`,
+/// reporting the remaining length from `Iterator::size_hint`.
+/// ```rust
+/// use strings::debug_iterator_truncation::truncated_iter_debug_fmt;
+/// let evens = (1..1000).filter(|i| i % 2 == 0);
+/// let debug_string = format!("result: {:?}", truncated_iter_debug_fmt(evens));
+/// assert!(debug_string.starts_with("result: (length≥43 truncated to 42) [2, 4, 6"));
+/// assert!(debug_string.ends_with("..."));
+/// ```
+pub fn truncated_iter_debug_fmt<II>(
+    into_iter: II,
+) -> IntoIterHolder<II, DEFAULT_ITER_DEBUG_FMT_MAX_ITEMS> {
+    truncated_iter_debug_fmt_reporting(into_iter, RemainingItems::SizeHint)
+}
+
+/// Truncates `IntoIterator` item length in debug string to maximum of `DEFAULT_ITER_DEBUG_FMT_MAX_ITEMS`,
+/// reporting the remaining items as specified.
+/// ```rust
+/// use strings::debug_iterator_truncation::{truncated_iter_debug_fmt_reporting, RemainingItems};
+/// let naturals = 1..;
+/// let debug_string = format!("{:?}", truncated_iter_debug_fmt_reporting(naturals, RemainingItems::Peek));
+/// assert!(debug_string.ends_with(", 41, 42]...and more"));
+/// ```
+pub fn truncated_iter_debug_fmt_reporting<II>(
+    into_iter: II,
+    remaining_items: RemainingItems,
+) -> IntoIterHolder<II, DEFAULT_ITER_DEBUG_FMT_MAX_ITEMS> {
+    IntoIterHolder::new(into_iter, remaining_items)
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_debug_iter_truncated_to_max_length {
+        use super::super::{
+            format_debug_iter_truncated_to_max_length, format_debug_iter_truncated_with_options,
+            RemainingItems,
+        };
+        use crate::debug_truncation_options::{TruncationOptions, ZeroLengthDisplay};
+        use std::collections::BTreeMap;
+        use std::fmt::{Debug, Formatter};
+        use std::ops::RangeInclusive;
+
+        /// Example struct which formats a filtered range with `TruncationOptions`.
+        struct StructWithOptions {
+            range: RangeInclusive<i32>,
+            options: TruncationOptions,
+            remaining_items: RemainingItems,
+        }
+
+        impl Debug for StructWithOptions {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_iter_truncated_with_options(
+                    f,
+                    self.range.clone().filter(|_| true),
+                    &self.options,
+                    self.remaining_items,
+                )
+            }
+        }
+
+        /// Example struct which formats the values of a range of a `BTreeMap`.
+        struct StructWithABTreeMap {
+            map: BTreeMap<i32, i32>,
+            max_length: usize,
+            remaining_items: RemainingItems,
+        }
+
+        impl StructWithABTreeMap {
+            fn new(
+                map: BTreeMap<i32, i32>,
+                max_length: usize,
+                remaining_items: RemainingItems,
+            ) -> Self {
+                Self {
+                    map,
+                    max_length,
+                    remaining_items,
+                }
+            }
+        }
+
+        impl Debug for StructWithABTreeMap {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_iter_truncated_to_max_length(
+                    f,
+                    self.map.range(2..).map(|(_, value)| value),
+                    self.max_length,
+                    self.remaining_items,
+                )
+            }
+        }
+
+        /// Helper function formatting the values of `inputs` from the second onwards.
+        fn truncate_debug_string_for(
+            inputs: &[i32],
+            max_length: usize,
+            remaining_items: RemainingItems,
+        ) -> String {
+            let map = inputs.iter().map(|&input| (input, input)).collect();
+            let input = StructWithABTreeMap::new(map, max_length, remaining_items);
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_max_length_longer_than_max_count() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5, 6], 3, RemainingItems::Count);
+
+            assert_eq!(result, "(length=5 truncated to 3) [2, 3, 4]...");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_max_length_longer_than_max_size_hint() {
+            let result =
+                truncate_debug_string_for(&[1, 2, 3, 4, 5, 6], 3, RemainingItems::SizeHint);
+
+            // `btree_map::Range` has no lower bound so all we know is that there is at least one more.
+            assert_eq!(result, "(length≥4 truncated to 3) [2, 3, 4]...");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_max_length_longer_than_max_peek() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5, 6], 3, RemainingItems::Peek);
+
+            assert_eq!(result, "[2, 3, 4]...and more");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_max_length_equal_max() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4], 3, RemainingItems::Count);
+
+            assert_eq!(result, "[2, 3, 4]");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_max_length_shorter_than_max() {
+            let result = truncate_debug_string_for(&[1, 2, 3], 3, RemainingItems::Peek);
+
+            assert_eq!(result, "[2, 3]");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_max_length_zero() {
+            let result = truncate_debug_string_for(&[1], 0, RemainingItems::Count);
+
+            assert_eq!(result, "[]");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_head_and_tail() {
+            let options = TruncationOptions::new().head_and_tail(2, 1);
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    range: 1..=10,
+                    options,
+                    remaining_items: RemainingItems::Peek,
+                }
+            );
+
+            assert_eq!(result, "(length=10 showing 2+1) [1, 2, ..., 10]");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_head_and_empty_tail() {
+            let options = TruncationOptions::new().head_and_tail(2, 0);
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    range: 1..=10,
+                    options,
+                    remaining_items: RemainingItems::Peek,
+                }
+            );
+
+            assert_eq!(result, "(length=10 showing 2+0) [1, 2, ...]");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_head_and_tail_unbounded_iterator() {
+            struct Naturals(TruncationOptions);
+
+            impl Debug for Naturals {
+                fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                    format_debug_iter_truncated_with_options(f, 1.., &self.0, RemainingItems::Peek)
+                }
+            }
+
+            let result = format!(
+                "{:?}",
+                Naturals(TruncationOptions::new().head_and_tail(2, 1))
+            );
+
+            assert_eq!(result, "[1, 2]...and more");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_head_and_tail_equal_max() {
+            let options = TruncationOptions::new().head_and_tail(2, 1);
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    range: 1..=3,
+                    options,
+                    remaining_items: RemainingItems::Peek,
+                }
+            );
+
+            assert_eq!(result, "[1, 2, 3]");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_unbounded_head_and_tail() {
+            let options = TruncationOptions::new().head_and_tail(1, usize::MAX);
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    range: 1..=3,
+                    options,
+                    remaining_items: RemainingItems::Count,
+                }
+            );
+
+            assert_eq!(result, "[1, 2, 3]");
+        }
+
+        #[test]
+        fn debug_iter_truncated_with_options_without_header() {
+            let options = TruncationOptions::new().max_length(2).show_header(false);
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    range: 1..=10,
+                    options,
+                    remaining_items: RemainingItems::Count,
+                }
+            );
+
+            assert_eq!(result, "[1, 2]...");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_max_total_chars() {
+            let options = TruncationOptions::new().max_total_chars(3);
+            let format_with = |remaining_items| {
+                format!(
+                    "{:?}",
+                    StructWithOptions {
+                        range: 1..=10,
+                        options: options.clone(),
+                        remaining_items,
+                    }
+                )
+            };
+
+            assert_eq!(
+                format_with(RemainingItems::Count),
+                "(length=10, 3 shown within 3 chars) [1, 2, 3]..."
+            );
+            assert_eq!(format_with(RemainingItems::Peek), "[1, 2, 3]...and more");
+        }
+
+        #[test]
+        fn debug_iter_truncated_to_max_total_chars_with_tail() {
+            let options = TruncationOptions::new()
+                .head_and_tail(2, 2)
+                .max_total_chars(4);
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    range: 1..=10,
+                    options,
+                    remaining_items: RemainingItems::Peek,
+                }
+            );
+
+            assert_eq!(
+                result,
+                "(length=10, 3 shown within 4 chars) [1, 2, ..., 10]"
+            );
+        }
+
+        #[test]
+        fn debug_iter_truncated_with_options_zero_length_empty_container() {
+            let options = TruncationOptions::new()
+                .max_length(0)
+                .zero_length(ZeroLengthDisplay::EmptyContainer);
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    range: 1..=10,
+                    options,
+                    remaining_items: RemainingItems::Peek,
+                }
+            );
+
+            assert_eq!(result, "[]...and more");
+        }
+
+        #[test]
+        fn non_empty_debug_iter_truncated_to_max_length_zero() {
+            let count = truncate_debug_string_for(&[1, 2, 3], 0, RemainingItems::Count);
+            let size_hint = truncate_debug_string_for(&[1, 2, 3], 0, RemainingItems::SizeHint);
+            let peek = truncate_debug_string_for(&[1, 2, 3], 0, RemainingItems::Peek);
+
+            assert_eq!(count, "(length=2)");
+            assert_eq!(size_hint, "(length≥1)");
+            assert_eq!(peek, "...and more");
+        }
+    }
+
+    mod test_truncated_iterator {
+        use super::super::{IntoIterHolder, RemainingItems};
+
+        const MAX_TEST_ITEMS: usize = 5;
+
+        /// Truncates `IntoIterator` item length in debug string to maximum of `MAX_TEST_ITEMS`.
+        fn test_truncated_debug<II>(
+            into_iter: II,
+            remaining_items: RemainingItems,
+        ) -> IntoIterHolder<II, MAX_TEST_ITEMS> {
+            IntoIterHolder::<II, MAX_TEST_ITEMS>::new(into_iter, remaining_items)
+        }
+
+        #[test]
+        fn truncated_iterator_chain_size_hint() {
+            let chain = [1, 2, 3].iter().chain([4, 5, 6, 7].iter());
+            let result = format!(
+                "{:?}",
+                test_truncated_debug(chain, RemainingItems::SizeHint)
+            );
+
+            assert_eq!(result, "(length≥7 truncated to 5) [1, 2, 3, 4, 5]...");
+        }
+
+        #[test]
+        fn truncated_iterator_infinite_peek() {
+            let result = format!("{:?}", test_truncated_debug(1.., RemainingItems::Peek));
+
+            assert_eq!(result, "[1, 2, 3, 4, 5]...and more");
+        }
+
+        #[test]
+        fn truncated_iterator_infinite_peek_alternate_upper_hex() {
+            let result = format!("{:#X?}", test_truncated_debug(10.., RemainingItems::Peek));
+
+            assert_eq!(
+                result,
+                "[\n    0xA,\n    0xB,\n    0xC,\n    0xD,\n    0xE,\n    ...and more,\n]"
+            );
+        }
+
+        #[test]
+        fn truncated_iterator_precision_overrides_max_length() {
+            let result = format!("{:.2?}", test_truncated_debug(1.., RemainingItems::Peek));
+
+            assert_eq!(result, "[1, 2]...and more");
+        }
+
+        #[test]
+        fn truncated_iterator_formats_repeatedly() {
+            let flat_map = (1..=3).flat_map(|i| vec![i; i]);
+            let holder = test_truncated_debug(flat_map, RemainingItems::Count);
+
+            assert_eq!(
+                format!("{:?}", holder),
+                "(length=6 truncated to 5) [1, 2, 2, 3, 3]..."
+            );
+            assert_eq!(
+                format!("{:?}", holder),
+                "(length=6 truncated to 5) [1, 2, 2, 3, 3]..."
+            );
+        }
+    }
+}
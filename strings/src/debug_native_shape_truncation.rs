@@ -0,0 +1,284 @@
+use crate::debug_truncation::{
+    format_debug_map_truncated_with_options, format_debug_shaped_truncated_with_options,
+    ContainerShape, DEFAULT_DEBUG_FMT_MAX_ITEMS,
+};
+use crate::debug_truncation_options::TruncationOptions;
+use linked_hash_set::LinkedHashSet;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::fmt::{Debug, Formatter};
+use std::hash::{BuildHasher, Hash};
+
+/// Collections which know the shape of their own `Debug` output,
+/// so their truncated output matches it apart from the truncation marker.
+pub trait NativeShapeTruncateDebug {
+    /// Number of items, as written in the `(length=N)` header.
+    fn native_length(&self) -> usize;
+
+    fn format_debug_native_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error>;
+
+    fn format_debug_native_truncated_to_max_length(
+        &self,
+        f: &mut Formatter<'_>,
+        max_length: usize,
+    ) -> Result<(), std::fmt::Error> {
+        let options = TruncationOptions::new().max_length(max_length);
+        self.format_debug_native_truncated_with_options(f, &options)
+    }
+}
+
+macro_rules! impl_native_shape_truncate_debug {
+    ($shape:expr, [$($generics:tt)*] $collection:ty) => {
+        impl<$($generics)*> NativeShapeTruncateDebug for $collection {
+            fn native_length(&self) -> usize {
+                self.len()
+            }
+
+            fn format_debug_native_truncated_with_options(
+                &self,
+                f: &mut Formatter<'_>,
+                options: &TruncationOptions,
+            ) -> Result<(), std::fmt::Error> {
+                format_debug_shaped_truncated_with_options(f, self, options, $shape)
+            }
+        }
+    };
+}
+
+impl_native_shape_truncate_debug!(ContainerShape::List, [D: Debug] [D]);
+impl_native_shape_truncate_debug!(ContainerShape::List, [D: Debug, const N: usize] [D; N]);
+impl_native_shape_truncate_debug!(ContainerShape::List, [D: Debug] Vec<D>);
+impl_native_shape_truncate_debug!(ContainerShape::List, [D: Debug] VecDeque<D>);
+impl_native_shape_truncate_debug!(ContainerShape::List, [D: Debug] LinkedList<D>);
+impl_native_shape_truncate_debug!(ContainerShape::List, [D: Debug] BinaryHeap<D>);
+impl_native_shape_truncate_debug!(ContainerShape::Set, [D: Debug, S] HashSet<D, S>);
+impl_native_shape_truncate_debug!(ContainerShape::Set, [D: Debug] BTreeSet<D>);
+impl_native_shape_truncate_debug!(
+    ContainerShape::Set,
+    [D: Debug + Eq + Hash, S: BuildHasher] LinkedHashSet<D, S>
+);
+
+impl<K: Debug, V: Debug, S> NativeShapeTruncateDebug for HashMap<K, V, S> {
+    fn native_length(&self) -> usize {
+        self.len()
+    }
+
+    fn format_debug_native_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        format_debug_map_truncated_with_options(f, self, options)
+    }
+}
+
+impl<K: Debug, V: Debug> NativeShapeTruncateDebug for BTreeMap<K, V> {
+    fn native_length(&self) -> usize {
+        self.len()
+    }
+
+    fn format_debug_native_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        format_debug_map_truncated_with_options(f, self, options)
+    }
+}
+
+impl<C: NativeShapeTruncateDebug + ?Sized> NativeShapeTruncateDebug for &C {
+    fn native_length(&self) -> usize {
+        (**self).native_length()
+    }
+
+    fn format_debug_native_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        (**self).format_debug_native_truncated_with_options(f, options)
+    }
+}
+
+/// Truncate a borrowed collection to `SIZE` items, or to the format precision if set,
+/// keeping the collection's own delimiters.
+pub struct NativeShapeHolder<'collection, C: ?Sized, const SIZE: usize> {
+    collection: &'collection C,
+    options: TruncationOptions,
+}
+
+impl<'collection, C: ?Sized, const SIZE: usize> NativeShapeHolder<'collection, C, SIZE> {
+    pub fn new(collection: &'collection C) -> Self {
+        Self::with_options(collection, TruncationOptions::new().max_length(SIZE))
+    }
+
+    /// Truncate as specified by `options` rather than to `SIZE`.
+    pub fn with_options(collection: &'collection C, options: TruncationOptions) -> Self {
+        Self {
+            collection,
+            options,
+        }
+    }
+}
+
+impl<'collection, C, const SIZE: usize> Debug for NativeShapeHolder<'collection, C, SIZE>
+where
+    C: NativeShapeTruncateDebug + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        self.collection
+            .format_debug_native_truncated_with_options(f, &options)
+    }
+}
+
+/// Truncates a collection's item length in debug string to maximum of `DEFAULT_DEBUG_FMT_MAX_ITEMS`,
+/// using `[]` for lists, `{}` for sets and `{k: v}` for maps, as the collection's own `Debug` does.
+/// ```rust
+/// use std::collections::BTreeSet;
+/// use strings::debug_native_shape_truncation::truncated_native_debug_fmt;
+/// let data = (1..=4).collect::<BTreeSet<_>>();
+/// let debug_string = format!("result: {:?}", truncated_native_debug_fmt(&data));
+/// assert_eq!(debug_string, "result: {1, 2, 3, 4}");
+/// ```
+pub fn truncated_native_debug_fmt<C: ?Sized>(
+    collection: &C,
+) -> NativeShapeHolder<'_, C, DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+    NativeShapeHolder::new(collection)
+}
+
+/// Truncates a collection's item length in debug string as specified by `options`,
+/// keeping the collection's own delimiters.
+pub fn truncated_native_debug_with_options<C: ?Sized>(
+    collection: &C,
+    options: TruncationOptions,
+) -> NativeShapeHolder<'_, C, DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+    NativeShapeHolder::with_options(collection, options)
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_debug_native_shape_truncated_to_max_length {
+        use super::super::{NativeShapeHolder, NativeShapeTruncateDebug};
+        use crate::debug_truncation_options::TruncationOptions;
+        use linked_hash_set::LinkedHashSet;
+        use std::collections::{BTreeMap, VecDeque};
+
+        fn truncate_debug_string_for<C: NativeShapeTruncateDebug>(
+            collection: C,
+            max_length: usize,
+        ) -> String {
+            let options = TruncationOptions::new().max_length(max_length);
+            format!(
+                "{:?}",
+                NativeShapeHolder::<_, 0>::with_options(&collection, options)
+            )
+        }
+
+        #[test]
+        fn debug_linked_hash_set_truncated_to_max_length_longer_than_max() {
+            let hash_set = (1..=6).collect::<LinkedHashSet<_>>();
+            let result = truncate_debug_string_for(hash_set, 5);
+
+            assert_eq!(result, "(length=6 truncated to 5) {1, 2, 3, 4, 5}...");
+        }
+
+        #[test]
+        fn debug_linked_hash_set_truncated_to_max_length_matches_untruncated() {
+            let hash_set = (1..=5).collect::<LinkedHashSet<_>>();
+            let expected = format!("{:?}", hash_set);
+            let result = truncate_debug_string_for(hash_set, 5);
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn debug_vec_deque_truncated_to_max_length_matches_untruncated() {
+            let vec_deque = (1..=5).collect::<VecDeque<_>>();
+            let expected = format!("{:?}", vec_deque);
+            let result = truncate_debug_string_for(vec_deque, 5);
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn debug_btree_map_truncated_to_max_length_longer_than_max() {
+            let map = (1..=3).map(|i| (i, -i)).collect::<BTreeMap<_, _>>();
+            let result = truncate_debug_string_for(map, 2);
+
+            assert_eq!(result, "(length=3 truncated to 2) {1: -1, 2: -2}...");
+        }
+
+        #[test]
+        fn debug_btree_map_truncated_to_max_length_matches_untruncated() {
+            let map = (1..=3).map(|i| (i, -i)).collect::<BTreeMap<_, _>>();
+            let expected = format!("{:?}", map);
+            let result = truncate_debug_string_for(map, 3);
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn empty_debug_linked_hash_set_truncated_to_max_length_zero() {
+            let result = truncate_debug_string_for(LinkedHashSet::<i32>::new(), 0);
+
+            assert_eq!(result, "{}");
+        }
+    }
+
+    mod test_truncated_native_shape {
+        use super::super::NativeShapeHolder;
+        use crate::debug_truncation_options::TruncationOptions;
+        use std::collections::HashSet;
+
+        const MAX_TEST_ITEMS: usize = 2;
+
+        fn test_truncated_native_debug<C: ?Sized>(
+            collection: &C,
+        ) -> NativeShapeHolder<'_, C, MAX_TEST_ITEMS> {
+            NativeShapeHolder::new(collection)
+        }
+
+        #[test]
+        fn truncated_native_slice() {
+            let result = format!("{:?}", test_truncated_native_debug(&[1, 2, 3][..]));
+
+            assert_eq!(result, "(length=3 truncated to 2) [1, 2]...");
+        }
+
+        #[test]
+        fn truncated_native_slice_precision_overrides_max_length() {
+            let result = format!("{:.1?}", test_truncated_native_debug(&[1, 2, 3][..]));
+
+            assert_eq!(result, "(length=3 truncated to 1) [1]...");
+        }
+
+        #[test]
+        fn truncated_native_btree_set_with_options() {
+            let set = (1..=5).collect::<std::collections::BTreeSet<_>>();
+            let options = TruncationOptions::new().head_and_tail(1, 1);
+            let result = format!(
+                "{:?}",
+                NativeShapeHolder::<_, 0>::with_options(&set, options)
+            );
+
+            assert_eq!(result, "(length=5 showing 1+1) {1, ..., 5}");
+        }
+
+        #[test]
+        fn truncated_native_hash_set() {
+            let hash_set = (1..=3).collect::<HashSet<_>>();
+            let result = format!("{:?}", test_truncated_native_debug(&hash_set));
+
+            assert!(
+                result.starts_with("(length=3 truncated to 2) {"),
+                "{}",
+                result
+            );
+            assert!(result.ends_with("}..."), "{}", result);
+        }
+    }
+}
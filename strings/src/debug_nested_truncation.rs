@@ -0,0 +1,177 @@
+use crate::debug_recursive_truncation::{DebugTruncated, TruncationContext};
+use crate::debug_truncation_options::TruncationOptions;
+use std::fmt::{Debug, Formatter};
+
+/// Truncate a borrowed collection of collections to the given limit at each level,
+/// or every level to the format precision if set.
+/// Any `DebugTruncated` value can be held, e.g. `Vec<Vec<T>>` for a `T` used as a leaf.
+pub struct NestedShapeHolder<'collection, 'limits, C: ?Sized> {
+    collection: &'collection C,
+    max_lengths: &'limits [usize],
+}
+
+impl<'collection, 'limits, C: ?Sized> NestedShapeHolder<'collection, 'limits, C> {
+    pub fn new(collection: &'collection C, max_lengths: &'limits [usize]) -> Self {
+        Self {
+            collection,
+            max_lengths,
+        }
+    }
+}
+
+impl<'collection, 'limits, C> Debug for NestedShapeHolder<'collection, 'limits, C>
+where
+    C: DebugTruncated + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let context = match f.precision() {
+            Some(precision) => {
+                TruncationContext::new(TruncationOptions::new().max_length(precision))
+            }
+            None => TruncationContext::with_max_lengths(TruncationOptions::new(), self.max_lengths),
+        };
+        self.collection.fmt_truncated(f, &context)
+    }
+}
+
+/// Truncates a collection of collections in debug string to `max_lengths[0]` items at the outer level,
+/// `max_lengths[1]` items in each collection it holds, and so on, each level with its own header.
+/// Collections nested deeper than `max_lengths` are not truncated.
+/// ```rust
+/// use strings::debug_nested_truncation::truncated_nested_debug_fmt;
+/// let data = vec![vec![1, 2, 3], vec![4], vec![5, 6]];
+/// assert_eq!(
+///     format!("{:?}", truncated_nested_debug_fmt(&data, &[2, 1])),
+///     "(length=3 truncated to 2) [(length=3 truncated to 1) [1]..., [4]]..."
+/// );
+/// ```
+pub fn truncated_nested_debug_fmt<'collection, 'limits, C: ?Sized>(
+    collection: &'collection C,
+    max_lengths: &'limits [usize],
+) -> NestedShapeHolder<'collection, 'limits, C> {
+    NestedShapeHolder::new(collection, max_lengths)
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_debug_nested_truncated {
+        use super::super::truncated_nested_debug_fmt;
+        use crate::debug_recursive_truncation::DebugTruncated;
+        use linked_hash_set::LinkedHashSet;
+        use std::collections::HashMap;
+
+        fn truncate_debug_string_for<C: DebugTruncated>(
+            collection: C,
+            max_lengths: &[usize],
+        ) -> String {
+            format!("{:?}", truncated_nested_debug_fmt(&collection, max_lengths))
+        }
+
+        fn vec_of_vecs() -> Vec<Vec<Vec<i32>>> {
+            (1..=3)
+                .map(|i| {
+                    (1..=3)
+                        .map(|j| (1..=3).map(|k| i * 100 + j * 10 + k).collect())
+                        .collect()
+                })
+                .collect()
+        }
+
+        #[test]
+        fn debug_nested_vec_truncated_at_each_level() {
+            let result = truncate_debug_string_for(vec_of_vecs(), &[1, 2, 1]);
+
+            assert_eq!(
+                result,
+                "(length=3 truncated to 1) [(length=3 truncated to 2) [\
+                 (length=3 truncated to 1) [111]..., \
+                 (length=3 truncated to 1) [121]...\
+                 ]...]..."
+            );
+        }
+
+        #[test]
+        fn debug_nested_vec_deeper_than_max_lengths_not_truncated() {
+            let result = truncate_debug_string_for(vec_of_vecs(), &[1, 1]);
+
+            assert_eq!(
+                result,
+                "(length=3 truncated to 1) [(length=3 truncated to 1) [[111, 112, 113]]...]..."
+            );
+        }
+
+        #[test]
+        fn debug_nested_vec_matches_untruncated() {
+            let expected = format!("{:?}", vec_of_vecs());
+            let result = truncate_debug_string_for(vec_of_vecs(), &[]);
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn debug_nested_hash_map_of_vecs_truncated() {
+            let map = vec![("a", vec![1, 2, 3])]
+                .into_iter()
+                .collect::<HashMap<_, _>>();
+            let result = truncate_debug_string_for(map, &[5, 2]);
+
+            assert_eq!(result, "{\"a\": (length=3 truncated to 2) [1, 2]...}");
+        }
+
+        #[test]
+        fn debug_nested_linked_hash_set_of_vecs_truncated() {
+            let set = (1..=3).map(|i| vec![i; 3]).collect::<LinkedHashSet<_>>();
+            let result = truncate_debug_string_for(set, &[2, 1]);
+
+            assert_eq!(
+                result,
+                "(length=3 truncated to 2) {\
+                 (length=3 truncated to 1) [1]..., \
+                 (length=3 truncated to 1) [2]...\
+                 }..."
+            );
+        }
+
+        #[test]
+        fn debug_nested_vec_of_strs_pretty() {
+            let nested = vec![vec!["a", "b"]];
+            let result = format!("{:#?}", truncated_nested_debug_fmt(&nested, &[1, 1]));
+
+            assert_eq!(
+                result,
+                "[\n    (length=2 truncated to 1) [\n        \"a\",\n        ...,\n    ],\n]"
+            );
+        }
+
+        #[test]
+        fn debug_nested_vec_of_user_leaves() {
+            #[derive(Debug)]
+            #[allow(dead_code)]
+            struct Leaf(i32);
+            impl DebugTruncated for Leaf {}
+
+            let nested = vec![vec![Leaf(1), Leaf(2)], vec![]];
+            let result = truncate_debug_string_for(nested, &[1, 1]);
+
+            assert_eq!(
+                result,
+                "(length=2 truncated to 1) [(length=2 truncated to 1) [Leaf(1)]...]..."
+            );
+        }
+
+        #[test]
+        fn debug_nested_precision_overrides_max_lengths() {
+            let result = format!(
+                "{:.1?}",
+                truncated_nested_debug_fmt(&vec_of_vecs(), &[2, 2, 2])
+            );
+
+            assert_eq!(
+                result,
+                "(length=3 truncated to 1) [(length=3 truncated to 1) [\
+                 (length=3 truncated to 1) [111]...\
+                 ]...]..."
+            );
+        }
+    }
+}
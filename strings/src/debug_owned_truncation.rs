@@ -0,0 +1,204 @@
+use crate::debug_native_shape_truncation::NativeShapeTruncateDebug;
+use crate::debug_truncation::DEFAULT_DEBUG_FMT_MAX_ITEMS;
+use crate::debug_truncation_options::TruncationOptions;
+use std::fmt::{Debug, Formatter};
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+
+/// Owns a collection, dereferencing to it, and truncates its debug string to `MAX_LENGTH` items,
+/// or to the format precision if set, keeping the collection's own delimiters.
+/// So a struct field declared as e.g. `Truncated<Vec<Item>, 20>` is truncated by `#[derive(Debug)]`.
+/// With the `serde` feature it is serialized and deserialized as the collection itself.
+/// ```rust
+/// use strings::debug_owned_truncation::Truncated;
+/// #[derive(Debug, Default)]
+/// struct Order {
+///     items: Truncated<Vec<i32>, 2>,
+/// }
+/// let mut order = Order::default();
+/// order.items.extend(1..=3);
+/// assert_eq!(
+///     format!("{:?}", order),
+///     "Order { items: (length=3 truncated to 2) [1, 2]... }"
+/// );
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Truncated<C, const MAX_LENGTH: usize = DEFAULT_DEBUG_FMT_MAX_ITEMS>(C);
+
+impl<C, const MAX_LENGTH: usize> Truncated<C, MAX_LENGTH> {
+    pub fn new(collection: C) -> Self {
+        Self(collection)
+    }
+
+    pub fn into_inner(self) -> C {
+        self.0
+    }
+}
+
+impl<C, const MAX_LENGTH: usize> From<C> for Truncated<C, MAX_LENGTH> {
+    fn from(collection: C) -> Self {
+        Self(collection)
+    }
+}
+
+impl<C, const MAX_LENGTH: usize> Deref for Truncated<C, MAX_LENGTH> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C, const MAX_LENGTH: usize> DerefMut for Truncated<C, MAX_LENGTH> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<C: NativeShapeTruncateDebug, const MAX_LENGTH: usize> Debug for Truncated<C, MAX_LENGTH> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = TruncationOptions::new().max_length(f.precision().unwrap_or(MAX_LENGTH));
+        self.0
+            .format_debug_native_truncated_with_options(f, &options)
+    }
+}
+
+impl<T, C: FromIterator<T>, const MAX_LENGTH: usize> FromIterator<T> for Truncated<C, MAX_LENGTH> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(C::from_iter(iter))
+    }
+}
+
+impl<C: IntoIterator, const MAX_LENGTH: usize> IntoIterator for Truncated<C, MAX_LENGTH> {
+    type Item = C::Item;
+    type IntoIter = C::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'collection, C, const MAX_LENGTH: usize> IntoIterator for &'collection Truncated<C, MAX_LENGTH>
+where
+    &'collection C: IntoIterator,
+{
+    type Item = <&'collection C as IntoIterator>::Item;
+    type IntoIter = <&'collection C as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'collection, C, const MAX_LENGTH: usize> IntoIterator
+    for &'collection mut Truncated<C, MAX_LENGTH>
+where
+    &'collection mut C: IntoIterator,
+{
+    type Item = <&'collection mut C as IntoIterator>::Item;
+    type IntoIter = <&'collection mut C as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&mut self.0).into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_truncated_owned {
+        use super::super::Truncated;
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::{BTreeMap, HashSet};
+        use std::hash::{Hash, Hasher};
+
+        #[derive(Debug, Clone, Default, PartialEq, Hash)]
+        struct StructWithTruncatedFields {
+            vec: Truncated<Vec<i32>, 2>,
+            map: Truncated<BTreeMap<i32, &'static str>, 1>,
+        }
+
+        fn struct_with_truncated_fields() -> StructWithTruncatedFields {
+            StructWithTruncatedFields {
+                vec: (1..=3).collect(),
+                map: vec![(1, "a"), (2, "b")].into_iter().collect(),
+            }
+        }
+
+        fn hash_of<T: Hash>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        #[test]
+        fn truncated_owned_fields_with_derived_debug() {
+            let result = format!("{:?}", struct_with_truncated_fields());
+
+            assert_eq!(
+                result,
+                "StructWithTruncatedFields { \
+                 vec: (length=3 truncated to 2) [1, 2]..., \
+                 map: (length=2 truncated to 1) {1: \"a\"}... }"
+            );
+        }
+
+        #[test]
+        fn truncated_owned_default_max_length() {
+            let truncated = (1..=50).collect::<Truncated<HashSet<_>>>();
+            let result = format!("{:?}", truncated);
+
+            assert!(
+                result.starts_with("(length=50 truncated to 42) {"),
+                "{}",
+                result
+            );
+        }
+
+        #[test]
+        fn truncated_owned_precision_overrides_max_length() {
+            let truncated = Truncated::<_, 2>::new(vec![1, 2, 3]);
+            let result = format!("{:.1?}", truncated);
+
+            assert_eq!(result, "(length=3 truncated to 1) [1]...");
+        }
+
+        #[test]
+        fn truncated_owned_derefs_to_collection() {
+            let mut truncated = Truncated::<Vec<i32>, 2>::default();
+            truncated.push(1);
+            for item in &mut truncated {
+                *item += 1;
+            }
+
+            assert_eq!(truncated.len(), 1);
+            assert_eq!((&truncated).into_iter().sum::<i32>(), 2);
+            assert_eq!(truncated.into_inner(), vec![2]);
+        }
+
+        #[test]
+        fn truncated_owned_compares_and_hashes_as_collection() {
+            let truncated = struct_with_truncated_fields();
+
+            assert_eq!(truncated.clone(), truncated);
+            assert_eq!(hash_of(&truncated.vec), hash_of(&vec![1, 2, 3]));
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn truncated_owned_serde_is_transparent() {
+            let truncated = Truncated::<Vec<i32>, 2>::new(vec![1, 2, 3]);
+            let json = serde_json::to_string(&truncated).unwrap();
+
+            assert_eq!(json, "[1,2,3]");
+            assert_eq!(
+                serde_json::from_str::<Truncated<Vec<i32>, 2>>(&json).unwrap(),
+                truncated
+            );
+        }
+    }
+}
@@ -0,0 +1,598 @@
+use crate::debug_truncation::{
+    format_debug_map_truncated_with_options, format_debug_shaped_truncated_with_options,
+    ContainerShape, DebugFlags,
+};
+use crate::debug_truncation_options::TruncationOptions;
+use linked_hash_set::LinkedHashSet;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Debug, Formatter};
+use std::hash::{BuildHasher, Hash};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Settings passed down through every level of a `DebugTruncated` value.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TruncationContext {
+    options: TruncationOptions,
+    /// Item limit of each nesting level, outermost first, replacing that of `options`, if set.
+    max_lengths: Option<Vec<usize>>,
+    level: usize,
+}
+
+impl TruncationContext {
+    /// Truncate every collection, at any depth, as specified by `options`.
+    pub fn new(options: TruncationOptions) -> Self {
+        Self {
+            options,
+            max_lengths: None,
+            level: 0,
+        }
+    }
+
+    /// Truncate the outermost collection to `max_lengths[0]` items, the collections it holds
+    /// to `max_lengths[1]` items, and so on, otherwise as specified by `options`,
+    /// e.g. `Vec<Vec<T>>` with `[10, 5]` shows 10 inner lists of at most 5 items each.
+    /// Collections nested deeper than `max_lengths` are not truncated.
+    pub fn with_max_lengths(
+        options: TruncationOptions,
+        max_lengths: impl Into<Vec<usize>>,
+    ) -> Self {
+        Self {
+            options,
+            max_lengths: Some(max_lengths.into()),
+            level: 0,
+        }
+    }
+
+    /// Options for the collections at this level.
+    pub fn options(&self) -> Cow<'_, TruncationOptions> {
+        match &self.max_lengths {
+            Some(max_lengths) => {
+                let max_length = max_lengths.get(self.level).copied().unwrap_or(usize::MAX);
+                Cow::Owned(self.options.clone().max_length(max_length))
+            }
+            None => Cow::Borrowed(&self.options),
+        }
+    }
+
+    /// Context for the items of a collection at this level.
+    pub fn next_level(&self) -> Cow<'_, Self> {
+        match self.max_lengths {
+            Some(_) => Cow::Owned(Self {
+                level: self.level + 1,
+                ..self.clone()
+            }),
+            None => Cow::Borrowed(self),
+        }
+    }
+}
+
+/// Values whose `Debug` output is truncated at every level, so e.g. `Option<Result<Vec<HashMap<K, V>>>>`
+/// has both the list and each map truncated in one call.
+/// Implemented for std containers and wrappers, and for primitives, strings, paths, times and addresses.
+/// Any other `Debug` type is written as is by the provided `fmt_truncated`,
+/// so can be used as a leaf with an empty impl, or `impl_debug_truncated_leaf!`,
+/// or by wrapping it in `DebugLeaf`.
+pub trait DebugTruncated: Debug {
+    fn fmt_truncated(
+        &self,
+        f: &mut Formatter<'_>,
+        _context: &TruncationContext,
+    ) -> Result<(), std::fmt::Error> {
+        self.fmt(f)
+    }
+}
+
+/// Formats any `Debug` value as is, wherever a `DebugTruncated` is needed.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DebugLeaf<T>(pub T);
+
+impl<T: Debug> Debug for DebugLeaf<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: Debug> DebugTruncated for DebugLeaf<T> {}
+
+/// Value formatted with `DebugTruncated::fmt_truncated` wherever a `Debug` is needed.
+struct TruncatedElement<'element, 'context, T: ?Sized>(&'element T, &'context TruncationContext);
+
+impl<'element, 'context, T> Debug for TruncatedElement<'element, 'context, T>
+where
+    T: DebugTruncated + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.0.fmt_truncated(f, self.1)
+    }
+}
+
+/// Implement `DebugTruncated` for `Debug` types written as is, e.g. `impl_debug_truncated_leaf!(MyId, MyName);`.
+#[macro_export]
+macro_rules! impl_debug_truncated_leaf {
+    ($($leaf:ty),* $(,)?) => {
+        $(
+            impl $crate::debug_recursive_truncation::DebugTruncated for $leaf {}
+        )*
+    };
+}
+
+impl_debug_truncated_leaf!(
+    (),
+    bool,
+    char,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f32,
+    f64,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    str,
+    String,
+    OsStr,
+    OsString,
+    Path,
+    PathBuf,
+    Duration,
+    Instant,
+    SystemTime,
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    Ordering
+);
+
+macro_rules! impl_debug_truncated_pointer {
+    ($([$($generics:tt)*] $pointer:ty),*) => {
+        $(
+            impl<$($generics)*> DebugTruncated for $pointer {
+                fn fmt_truncated(
+                    &self,
+                    f: &mut Formatter<'_>,
+                    context: &TruncationContext,
+                ) -> Result<(), std::fmt::Error> {
+                    (**self).fmt_truncated(f, context)
+                }
+            }
+        )*
+    };
+}
+
+impl_debug_truncated_pointer!(
+    [T: DebugTruncated + ?Sized] &T,
+    [T: DebugTruncated + ?Sized] &mut T,
+    [T: DebugTruncated + ?Sized] Box<T>,
+    [T: DebugTruncated + ?Sized] Rc<T>,
+    [T: DebugTruncated + ?Sized] Arc<T>
+);
+
+impl<'borrowed, B> DebugTruncated for Cow<'borrowed, B>
+where
+    B: DebugTruncated + ToOwned + ?Sized,
+    B::Owned: Debug,
+{
+    fn fmt_truncated(
+        &self,
+        f: &mut Formatter<'_>,
+        context: &TruncationContext,
+    ) -> Result<(), std::fmt::Error> {
+        (**self).fmt_truncated(f, context)
+    }
+}
+
+impl<T: DebugTruncated> DebugTruncated for Option<T> {
+    fn fmt_truncated(
+        &self,
+        f: &mut Formatter<'_>,
+        context: &TruncationContext,
+    ) -> Result<(), std::fmt::Error> {
+        match self {
+            Some(value) => f
+                .debug_tuple("Some")
+                .field(&TruncatedElement(value, context))
+                .finish(),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+impl<T: DebugTruncated, E: DebugTruncated> DebugTruncated for Result<T, E> {
+    fn fmt_truncated(
+        &self,
+        f: &mut Formatter<'_>,
+        context: &TruncationContext,
+    ) -> Result<(), std::fmt::Error> {
+        match self {
+            Ok(value) => f
+                .debug_tuple("Ok")
+                .field(&TruncatedElement(value, context))
+                .finish(),
+            Err(error) => f
+                .debug_tuple("Err")
+                .field(&TruncatedElement(error, context))
+                .finish(),
+        }
+    }
+}
+
+macro_rules! impl_debug_truncated_tuple {
+    ($(($($name:ident),+)),*) => {
+        $(
+            impl<$($name: DebugTruncated),+> DebugTruncated for ($($name,)+) {
+                #[allow(non_snake_case)]
+                fn fmt_truncated(
+                    &self,
+                    f: &mut Formatter<'_>,
+                    context: &TruncationContext,
+                ) -> Result<(), std::fmt::Error> {
+                    let ($($name,)+) = self;
+                    f.debug_tuple("")
+                        $(.field(&TruncatedElement($name, context)))+
+                        .finish()
+                }
+            }
+        )*
+    };
+}
+
+impl_debug_truncated_tuple!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F)
+);
+
+macro_rules! impl_debug_truncated_collection {
+    ($shape:expr, [$($generics:tt)*] $collection:ty) => {
+        impl<$($generics)*> DebugTruncated for $collection {
+            fn fmt_truncated(
+                &self,
+                f: &mut Formatter<'_>,
+                context: &TruncationContext,
+            ) -> Result<(), std::fmt::Error> {
+                let element_context = context.next_level();
+                let elements = self
+                    .iter()
+                    .map(|element| TruncatedElement(element, &element_context));
+                format_debug_shaped_truncated_with_options(f, elements, &context.options(), $shape)
+            }
+        }
+    };
+}
+
+impl_debug_truncated_collection!(ContainerShape::List, [D: DebugTruncated] [D]);
+impl_debug_truncated_collection!(
+    ContainerShape::List,
+    [D: DebugTruncated, const N: usize] [D; N]
+);
+impl_debug_truncated_collection!(ContainerShape::List, [D: DebugTruncated] Vec<D>);
+impl_debug_truncated_collection!(ContainerShape::List, [D: DebugTruncated] VecDeque<D>);
+impl_debug_truncated_collection!(ContainerShape::List, [D: DebugTruncated] LinkedList<D>);
+impl_debug_truncated_collection!(ContainerShape::List, [D: DebugTruncated] BinaryHeap<D>);
+impl_debug_truncated_collection!(ContainerShape::Set, [D: DebugTruncated, S] HashSet<D, S>);
+impl_debug_truncated_collection!(ContainerShape::Set, [D: DebugTruncated] BTreeSet<D>);
+impl_debug_truncated_collection!(
+    ContainerShape::Set,
+    [D: DebugTruncated + Eq + Hash, S: BuildHasher] LinkedHashSet<D, S>
+);
+
+impl<K: DebugTruncated, V: DebugTruncated, S> DebugTruncated for HashMap<K, V, S> {
+    fn fmt_truncated(
+        &self,
+        f: &mut Formatter<'_>,
+        context: &TruncationContext,
+    ) -> Result<(), std::fmt::Error> {
+        let entry_context = context.next_level();
+        let entries = self.iter().map(|(key, value)| {
+            (
+                TruncatedElement(key, &entry_context),
+                TruncatedElement(value, &entry_context),
+            )
+        });
+        format_debug_map_truncated_with_options(f, entries, &context.options())
+    }
+}
+
+impl<K: DebugTruncated, V: DebugTruncated> DebugTruncated for BTreeMap<K, V> {
+    fn fmt_truncated(
+        &self,
+        f: &mut Formatter<'_>,
+        context: &TruncationContext,
+    ) -> Result<(), std::fmt::Error> {
+        let entry_context = context.next_level();
+        let entries = self.iter().map(|(key, value)| {
+            (
+                TruncatedElement(key, &entry_context),
+                TruncatedElement(value, &entry_context),
+            )
+        });
+        format_debug_map_truncated_with_options(f, entries, &context.options())
+    }
+}
+
+/// Truncate a borrowed value at every level, as specified by a `TruncationContext`,
+/// with the item limit of every level replaced by the format precision if set.
+/// The precision is not passed on to the leaves, and a configured tail is kept.
+pub struct DebugTruncatedHolder<'value, T: ?Sized> {
+    value: &'value T,
+    context: TruncationContext,
+}
+
+impl<'value, T: ?Sized> DebugTruncatedHolder<'value, T> {
+    pub fn new(value: &'value T, context: TruncationContext) -> Self {
+        Self { value, context }
+    }
+}
+
+impl<'value, T: DebugTruncated + ?Sized> Debug for DebugTruncatedHolder<'value, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match f.precision() {
+            Some(_) => {
+                let context =
+                    TruncationContext::new(self.context.options.for_formatter(f).into_owned());
+                DebugFlags::of(f)
+                    .without_precision()
+                    .write(f, &TruncatedElement(self.value, &context))
+            }
+            None => self.value.fmt_truncated(f, &self.context),
+        }
+    }
+}
+
+/// Truncates every collection in a value's debug string, however deeply nested,
+/// to a maximum of `DEFAULT_DEBUG_FMT_MAX_ITEMS` items.
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use strings::debug_recursive_truncation::truncated_recursive_debug_fmt;
+/// let map = (1..=50).map(|i| (i, i * i)).collect::<BTreeMap<_, _>>();
+/// let data: Option<Result<Vec<_>, String>> = Some(Ok(vec![map; 3]));
+/// assert_eq!(
+///     format!("{:.1?}", truncated_recursive_debug_fmt(&data)),
+///     "Some(Ok((length=3 truncated to 1) [(length=50 truncated to 1) {1: 1}...]...))"
+/// );
+/// ```
+pub fn truncated_recursive_debug_fmt<T: ?Sized>(value: &T) -> DebugTruncatedHolder<'_, T> {
+    DebugTruncatedHolder::new(value, TruncationContext::default())
+}
+
+/// Truncates every collection in a value's debug string, however deeply nested, as specified by `options`.
+pub fn truncated_recursive_debug_with_options<T: ?Sized>(
+    value: &T,
+    options: TruncationOptions,
+) -> DebugTruncatedHolder<'_, T> {
+    DebugTruncatedHolder::new(value, TruncationContext::new(options))
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_debug_truncated {
+        use super::super::{DebugLeaf, DebugTruncated, DebugTruncatedHolder, TruncationContext};
+        use crate::debug_truncation_options::TruncationOptions;
+        use std::borrow::Cow;
+        use std::cmp::Ordering;
+        use std::collections::{BTreeMap, VecDeque};
+        use std::fmt::Debug;
+        use std::num::NonZeroU8;
+        use std::path::PathBuf;
+        use std::rc::Rc;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        fn truncate_debug_string_for<T: DebugTruncated>(value: T, max_length: usize) -> String {
+            let context = TruncationContext::new(TruncationOptions::new().max_length(max_length));
+            format!("{:?}", DebugTruncatedHolder::new(&value, context))
+        }
+
+        #[test]
+        fn debug_truncated_option_result_vec_map() {
+            let map = (1..=3).map(|i| (i, vec![i; 3])).collect::<BTreeMap<_, _>>();
+            let value: Option<Result<Vec<_>, ()>> = Some(Ok(vec![map.clone(), map]));
+            let result = truncate_debug_string_for(value, 1);
+
+            assert_eq!(
+                result,
+                "Some(Ok((length=2 truncated to 1) [\
+                 (length=3 truncated to 1) {1: (length=3 truncated to 1) [1]...}...\
+                 ]...))"
+            );
+        }
+
+        #[test]
+        fn debug_truncated_matches_untruncated() {
+            let value = (Some(vec![1, 2]), Err::<(), _>("e".to_string()), 'c');
+            let expected = format!("{:?}", value);
+            let result = truncate_debug_string_for(value, 2);
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn debug_truncated_single_tuple() {
+            let result = truncate_debug_string_for((vec![1, 2],), 1);
+
+            assert_eq!(result, "((length=2 truncated to 1) [1]...,)");
+        }
+
+        #[test]
+        fn debug_truncated_pointers() {
+            let value = (
+                Box::new(vec![1, 2]),
+                Rc::new(VecDeque::from(vec![3, 4])),
+                Arc::new([5, 6]),
+            );
+            let result = truncate_debug_string_for(value, 1);
+
+            assert_eq!(
+                result,
+                "((length=2 truncated to 1) [1]..., \
+                 (length=2 truncated to 1) [3]..., \
+                 (length=2 truncated to 1) [5]...)"
+            );
+        }
+
+        #[test]
+        fn debug_truncated_leaf() {
+            #[derive(Debug)]
+            struct Leaf {
+                #[allow(dead_code)]
+                items: Vec<i32>,
+            }
+
+            let value = Vec::<DebugLeaf<Leaf>>::new();
+            assert_eq!(truncate_debug_string_for(value, 1), "[]");
+
+            let value = Some(DebugLeaf(Leaf { items: vec![1, 2] }));
+            assert_eq!(
+                truncate_debug_string_for(value, 1),
+                "Some(Leaf { items: [1, 2] })"
+            );
+        }
+
+        #[test]
+        fn debug_truncated_std_leaves() {
+            let value = (
+                Duration::from_millis(1500),
+                PathBuf::from("/tmp/a"),
+                Cow::Borrowed("cow"),
+                NonZeroU8::new(7),
+                Ordering::Less,
+            );
+            assert_eq!(
+                truncate_debug_string_for(vec![value], 1),
+                "[(1.5s, \"/tmp/a\", \"cow\", Some(7), Less)]"
+            );
+
+            let value: Cow<'_, [i32]> = Cow::Owned(vec![1, 2, 3]);
+            assert_eq!(
+                truncate_debug_string_for(value, 1),
+                "(length=3 truncated to 1) [1]..."
+            );
+        }
+
+        #[test]
+        fn debug_truncated_user_leaves() {
+            #[derive(Debug)]
+            #[allow(dead_code)]
+            struct Point(i32, i32);
+            impl DebugTruncated for Point {}
+
+            #[derive(Debug)]
+            #[allow(dead_code)]
+            struct Name(&'static str);
+            crate::impl_debug_truncated_leaf!(Name);
+
+            let points = vec![Point(1, 2), Point(3, 4)];
+            assert_eq!(
+                truncate_debug_string_for(points, 1),
+                "(length=2 truncated to 1) [Point(1, 2)]..."
+            );
+
+            let names = Some(vec![(1..=2)
+                .map(|i| (i, Name("n")))
+                .collect::<BTreeMap<_, _>>()]);
+            assert_eq!(
+                truncate_debug_string_for(names, 1),
+                "Some([(length=2 truncated to 1) {1: Name(\"n\")}...])"
+            );
+        }
+
+        #[test]
+        fn debug_truncated_with_max_lengths() {
+            let value = Some(vec![vec![vec![1, 2, 3]; 3]; 3]);
+            let context = TruncationContext::with_max_lengths(TruncationOptions::new(), [2, 1]);
+            let result = format!("{:?}", DebugTruncatedHolder::new(&value, context));
+
+            assert_eq!(
+                result,
+                "Some((length=3 truncated to 2) [\
+                 (length=3 truncated to 1) [[1, 2, 3]]..., \
+                 (length=3 truncated to 1) [[1, 2, 3]]...\
+                 ]...)"
+            );
+        }
+    }
+
+    mod test_truncated_recursive {
+        use super::super::truncated_recursive_debug_with_options;
+        use crate::debug_truncation_options::TruncationOptions;
+
+        #[test]
+        fn truncated_recursive_with_options() {
+            let value = Some(vec![vec![1, 2, 3]; 3]);
+            let options = TruncationOptions::new().head_and_tail(1, 1);
+            let result = format!(
+                "{:?}",
+                truncated_recursive_debug_with_options(&value, options)
+            );
+
+            assert_eq!(
+                result,
+                "Some((length=3 showing 1+1) [\
+                 (length=3 showing 1+1) [1, ..., 3], \
+                 ..., \
+                 (length=3 showing 1+1) [1, ..., 3]\
+                 ])"
+            );
+        }
+
+        #[test]
+        fn truncated_recursive_precision_not_passed_to_leaves() {
+            let value = vec![vec![0.5, 1.5, 2.5]];
+            let options = TruncationOptions::new();
+            let result = format!(
+                "{:.2?}",
+                truncated_recursive_debug_with_options(&value, options)
+            );
+
+            assert_eq!(result, "[(length=3 truncated to 2) [0.5, 1.5]...]");
+        }
+
+        #[test]
+        fn truncated_recursive_pretty() {
+            let value = Some(vec![1, 2]);
+            let options = TruncationOptions::new().max_length(1);
+            let result = format!(
+                "{:#?}",
+                truncated_recursive_debug_with_options(&value, options)
+            );
+
+            assert_eq!(
+                result,
+                "Some(\n    (length=2 truncated to 1) [\n        1,\n        ...,\n    ],\n)"
+            );
+        }
+    }
+}
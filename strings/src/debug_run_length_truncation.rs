@@ -0,0 +1,224 @@
+use crate::debug_truncation::{format_debug_shaped_truncated_reporting, ContainerShape};
+use crate::debug_truncation_options::{ReportedLength, TruncationOptions};
+use std::fmt::{Debug, Formatter};
+use std::iter::Peekable;
+
+/// Write an `IntoIterator` of `Debug + PartialEq` to `Formatter`, collapsing each run of
+/// consecutive equal elements into one item, e.g. `[0 ×4096, 7, 8]`, then truncating to
+/// `max_length` runs, e.g. `(length=4098 in 3 runs truncated to 2) [0 ×4096, 7]...`.
+/// `into_iter` is cloned and iterated twice, first to count the runs, so it should be cheap to clone
+/// and yield the same elements both times, as a slice or a reference to a collection does.
+pub fn format_debug_run_length_truncated_to_max_length<D, II>(
+    f: &mut Formatter<'_>,
+    into_iter: II,
+    max_length: usize,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug + PartialEq,
+    II: IntoIterator<Item = D> + Clone,
+{
+    let options = TruncationOptions::new().max_length(max_length);
+    format_debug_run_length_truncated_with_options(f, into_iter, &options)
+}
+
+/// Write an `IntoIterator` of `Debug + PartialEq` to `Formatter`, collapsing each run of
+/// consecutive equal elements into one item, then truncating as specified by `options`,
+/// which count runs rather than elements.
+/// Sampled items would be labelled with their run index rather than their element index,
+/// so `options.sampling` is ignored.
+/// `into_iter` is cloned and iterated twice, as for `format_debug_run_length_truncated_to_max_length`.
+pub fn format_debug_run_length_truncated_with_options<D, II>(
+    f: &mut Formatter<'_>,
+    into_iter: II,
+    options: &TruncationOptions,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug + PartialEq,
+    II: IntoIterator<Item = D> + Clone,
+{
+    let mut length = 0;
+    let mut runs_length = 0;
+    let mut previous = None;
+    for element in into_iter.clone() {
+        length += 1;
+        if previous.as_ref() != Some(&element) {
+            runs_length += 1;
+        }
+        previous = Some(element);
+    }
+
+    let mut options = options.clone();
+    options.sampling = None;
+    let runs = Runs {
+        elements: into_iter.into_iter().peekable(),
+        remaining_runs: runs_length,
+    };
+    let reported_length = ReportedLength::Runs {
+        length,
+        runs: runs_length,
+    };
+    format_debug_shaped_truncated_reporting(
+        f,
+        runs,
+        &options,
+        ContainerShape::List,
+        reported_length,
+    )
+}
+
+/// Runs of consecutive equal elements, counted beforehand so the length is known.
+struct Runs<I: Iterator> {
+    elements: Peekable<I>,
+    remaining_runs: usize,
+}
+
+impl<I> Iterator for Runs<I>
+where
+    I: Iterator,
+    I::Item: PartialEq,
+{
+    type Item = Run<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.elements.next()?;
+        let mut count = 1;
+        while self.elements.next_if_eq(&element).is_some() {
+            count += 1;
+        }
+        self.remaining_runs -= 1;
+        Some(Run { element, count })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining_runs, Some(self.remaining_runs))
+    }
+}
+
+impl<I> ExactSizeIterator for Runs<I>
+where
+    I: Iterator,
+    I::Item: PartialEq,
+{
+}
+
+/// `count` consecutive equal elements, written as `element ×count`, or as `element` alone.
+struct Run<D> {
+    element: D,
+    count: usize,
+}
+
+impl<D: Debug> Debug for Run<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.element.fmt(f)?;
+        if self.count > 1 {
+            write!(f, " ×{}", self.count)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_debug_run_length_truncated {
+        use super::super::{
+            format_debug_run_length_truncated_to_max_length,
+            format_debug_run_length_truncated_with_options,
+        };
+        use crate::debug_truncation_options::{Sampling, TruncationOptions};
+        use std::collections::VecDeque;
+        use std::fmt::{Debug, Formatter};
+
+        /// Example struct which has a buffer field.
+        struct StructWithABuffer {
+            buffer: VecDeque<u8>,
+            max_length: usize,
+        }
+
+        impl Debug for StructWithABuffer {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_run_length_truncated_to_max_length(f, &self.buffer, self.max_length)
+            }
+        }
+
+        struct RunLengthWithOptions<'slice>(&'slice [i32], TruncationOptions);
+
+        impl<'slice> Debug for RunLengthWithOptions<'slice> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_run_length_truncated_with_options(f, self.0, &self.1)
+            }
+        }
+
+        fn truncate_debug_string_for(inputs: &[u8], max_length: usize) -> String {
+            let input = StructWithABuffer {
+                buffer: inputs.iter().copied().collect(),
+                max_length,
+            };
+            format!("{:?}", input)
+        }
+
+        #[test]
+        fn debug_run_length_collapses_runs_within_max_length() {
+            let mut inputs = vec![0; 4096];
+            inputs.extend([7, 8]);
+            let result = truncate_debug_string_for(&inputs, 3);
+
+            assert_eq!(result, "[0 ×4096, 7, 8]");
+        }
+
+        #[test]
+        fn debug_run_length_truncates_runs() {
+            let result = truncate_debug_string_for(&[0, 0, 0, 7, 8, 8], 2);
+
+            assert_eq!(result, "(length=6 in 3 runs truncated to 2) [0 ×3, 7]...");
+        }
+
+        #[test]
+        fn debug_run_length_zero_max_length() {
+            let result = truncate_debug_string_for(&[0, 0, 7], 0);
+
+            assert_eq!(result, "(length=3 in 2 runs)");
+        }
+
+        #[test]
+        fn debug_run_length_empty() {
+            let result = truncate_debug_string_for(&[], 2);
+
+            assert_eq!(result, "[]");
+        }
+
+        #[test]
+        fn debug_run_length_with_head_and_tail() {
+            let options = TruncationOptions::new().head_and_tail(1, 1);
+            let input = [1, 1, 2, 3, 3, 3];
+            let result = format!("{:?}", RunLengthWithOptions(&input, options));
+
+            assert_eq!(result, "(length=6 in 3 runs showing 1+1) [1 ×2, ..., 3 ×3]");
+        }
+
+        #[test]
+        fn debug_run_length_ignores_sampling() {
+            let options = TruncationOptions::new()
+                .max_length(2)
+                .sampling(Sampling::Stride);
+            let input = [1, 1, 2, 3, 3, 3];
+            let result = format!("{:?}", RunLengthWithOptions(&input, options));
+
+            assert_eq!(result, "(length=6 in 3 runs truncated to 2) [1 ×2, 2]...");
+        }
+
+        #[test]
+        fn debug_run_length_pretty() {
+            let input = StructWithABuffer {
+                buffer: vec![5, 5, 6].into_iter().collect(),
+                max_length: 1,
+            };
+            let result = format!("{:#?}", input);
+
+            assert_eq!(
+                result,
+                "(length=3 in 2 runs truncated to 1) [\n    5 ×2,\n    ...,\n]"
+            );
+        }
+    }
+}
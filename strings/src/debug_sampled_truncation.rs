@@ -0,0 +1,295 @@
+use crate::debug_truncation::{format_debug_truncated_with_options, ContainerShape};
+use crate::debug_truncation_options::{ReportedLength, Sampling, TruncationOptions};
+use std::fmt::{Debug, Formatter};
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter`,
+/// showing at most `max_length` items evenly spaced over the whole iterator, each with its index,
+/// e.g. `(length=10000 sampled 10 @stride 1000) [0: a, 1000: b, ..]...`.
+/// Item `i` shown is the one at `i * length / max_length`, so when `max_length` does not divide
+/// the length the gaps differ by one and the stride is written rounded down, e.g. `@stride ~2`.
+/// Items between those shown are skipped with `Iterator::nth`, so are never formatted.
+pub fn format_debug_sampled_by_stride<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    max_length: usize,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let options = TruncationOptions::new()
+        .max_length(max_length)
+        .sampling(Sampling::Stride);
+    format_debug_truncated_with_options(f, exact_into_iter, &options)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter`,
+/// showing `max_length` items picked at random, the same every time for the same `seed`,
+/// in their original order and each with its index, e.g. `(length=10000 sampled 10 @seed 42) [..]...`.
+pub fn format_debug_sampled_by_reservoir<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    max_length: usize,
+    seed: u64,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let options = TruncationOptions::new()
+        .max_length(max_length)
+        .sampling(Sampling::Reservoir { seed });
+    format_debug_truncated_with_options(f, exact_into_iter, &options)
+}
+
+/// Write `options.shown_length()` items of `exact_iter` picked by `sampling`,
+/// for iterators longer than that.
+pub(crate) fn format_debug_shaped_sampled<D, ESI>(
+    f: &mut Formatter<'_>,
+    exact_iter: ESI,
+    options: &TruncationOptions,
+    sampling: Sampling,
+    shape: ContainerShape,
+    reported_length: ReportedLength,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+{
+    let length = exact_iter.len();
+    let shown_length = options.shown_length();
+    match sampling {
+        Sampling::Stride => {
+            let stride = length / shown_length;
+            let approximate = match length % shown_length {
+                0 => "",
+                _ => "~",
+            };
+            options.format_sampled_header(
+                f,
+                reported_length,
+                shown_length,
+                format_args!("@stride {}{}", approximate, stride),
+            )?;
+            let mut exact_iter = exact_iter;
+            let mut next_index = 0;
+            let entries = (0..shown_length).filter_map(|step| {
+                let index = (step as u128 * length as u128 / shown_length as u128) as usize;
+                let element = exact_iter.nth(index - next_index)?;
+                next_index = index + 1;
+                Some(IndexedEntry(index, element))
+            });
+            shape.format_debug_truncated_entries(f, entries, &options.marker)
+        }
+        Sampling::Reservoir { seed } => {
+            let mut random = SplitMix64(seed);
+            let mut reservoir = Vec::with_capacity(shown_length);
+            for (index, element) in exact_iter.enumerate() {
+                if index < shown_length {
+                    reservoir.push(IndexedEntry(index, element));
+                } else {
+                    let replaced = random.below(index + 1);
+                    if replaced < shown_length {
+                        reservoir[replaced] = IndexedEntry(index, element);
+                    }
+                }
+            }
+            reservoir.sort_by_key(|entry| entry.0);
+            options.format_sampled_header(
+                f,
+                reported_length,
+                reservoir.len(),
+                format_args!("@seed {}", seed),
+            )?;
+            shape.format_debug_truncated_entries(f, reservoir, &options.marker)
+        }
+    }
+}
+
+/// Item written with its index, as `index: item`.
+struct IndexedEntry<D>(usize, D);
+
+impl<D: Debug> Debug for IndexedEntry<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}: ", self.0)?;
+        self.1.fmt(f)
+    }
+}
+
+/// SplitMix64, a small generator giving the same numbers on every platform for the same seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, with a bias too small to matter for sampling.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_debug_sampled_by_stride {
+        use super::super::format_debug_sampled_by_stride;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithARange {
+            length: usize,
+            max_length: usize,
+        }
+
+        impl Debug for StructWithARange {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_sampled_by_stride(f, 0..self.length, self.max_length)
+            }
+        }
+
+        fn truncate_debug_string_for(length: usize, max_length: usize) -> String {
+            let input = StructWithARange { length, max_length };
+            format!("{:?}", input)
+        }
+
+        #[test]
+        fn debug_sampled_by_stride_even_length() {
+            let result = truncate_debug_string_for(10000, 4);
+
+            assert_eq!(
+                result,
+                "(length=10000 sampled 4 @stride 2500) \
+                 [0: 0, 2500: 2500, 5000: 5000, 7500: 7500]..."
+            );
+        }
+
+        #[test]
+        fn debug_sampled_by_stride_uneven_length() {
+            let result = truncate_debug_string_for(10, 4);
+
+            assert_eq!(
+                result,
+                "(length=10 sampled 4 @stride ~2) [0: 0, 2: 2, 5: 5, 7: 7]..."
+            );
+        }
+
+        #[test]
+        fn debug_sampled_by_stride_one_longer_than_max_length() {
+            let result = truncate_debug_string_for(11, 10);
+
+            assert_eq!(
+                result,
+                "(length=11 sampled 10 @stride ~1) \
+                 [0: 0, 1: 1, 2: 2, 3: 3, 4: 4, 5: 5, 6: 6, 7: 7, 8: 8, 9: 9]..."
+            );
+        }
+
+        #[test]
+        fn debug_sampled_by_stride_fewer_than_max_length() {
+            let result = truncate_debug_string_for(7, 3);
+
+            assert_eq!(
+                result,
+                "(length=7 sampled 3 @stride ~2) [0: 0, 2: 2, 4: 4]..."
+            );
+        }
+
+        #[test]
+        fn debug_sampled_by_stride_within_max_length() {
+            let result = truncate_debug_string_for(3, 3);
+
+            assert_eq!(result, "[0, 1, 2]");
+        }
+
+        #[test]
+        fn debug_sampled_by_stride_zero_length() {
+            let result = truncate_debug_string_for(3, 0);
+
+            assert_eq!(result, "(length=3)");
+        }
+
+        #[test]
+        fn debug_sampled_by_stride_pretty() {
+            let input = StructWithARange {
+                length: 4,
+                max_length: 2,
+            };
+            let result = format!("{:#?}", input);
+
+            assert_eq!(
+                result,
+                "(length=4 sampled 2 @stride 2) [\n    0: 0,\n    2: 2,\n    ...,\n]"
+            );
+        }
+    }
+
+    mod test_debug_sampled_by_reservoir {
+        use super::super::format_debug_sampled_by_reservoir;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithARange {
+            length: usize,
+            max_length: usize,
+            seed: u64,
+        }
+
+        impl Debug for StructWithARange {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_sampled_by_reservoir(f, 0..self.length, self.max_length, self.seed)
+            }
+        }
+
+        fn truncate_debug_string_for(length: usize, max_length: usize, seed: u64) -> String {
+            let input = StructWithARange {
+                length,
+                max_length,
+                seed,
+            };
+            format!("{:?}", input)
+        }
+
+        #[test]
+        fn debug_sampled_by_reservoir_is_reproducible() {
+            let result = truncate_debug_string_for(10000, 5, 42);
+
+            assert_eq!(
+                result,
+                "(length=10000 sampled 5 @seed 42) \
+                 [2568: 2568, 3331: 3331, 6826: 6826, 8122: 8122, 8302: 8302]..."
+            );
+            assert_eq!(result, truncate_debug_string_for(10000, 5, 42));
+            assert_ne!(result, truncate_debug_string_for(10000, 5, 43));
+        }
+
+        #[test]
+        fn debug_sampled_by_reservoir_in_index_order() {
+            let result = truncate_debug_string_for(10000, 5, 42);
+            let entries = result
+                .trim_start_matches("(length=10000 sampled 5 @seed 42) [")
+                .trim_end_matches("]...")
+                .split(", ")
+                .map(|entry| entry.split(": ").next().unwrap().parse::<usize>().unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(entries.len(), 5, "{}", result);
+            assert!(
+                entries.windows(2).all(|pair| pair[0] < pair[1]),
+                "{}",
+                result
+            );
+        }
+
+        #[test]
+        fn debug_sampled_by_reservoir_within_max_length() {
+            let result = truncate_debug_string_for(3, 3, 42);
+
+            assert_eq!(result, "[0, 1, 2]");
+        }
+    }
+}
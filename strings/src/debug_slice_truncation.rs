@@ -1,88 +1,149 @@
+use crate::debug_run_length_truncation::{
+    format_debug_run_length_truncated_to_max_length, format_debug_run_length_truncated_with_options,
+};
+use crate::debug_sampled_truncation::{
+    format_debug_sampled_by_reservoir, format_debug_sampled_by_stride,
+};
+use crate::debug_truncation::{
+    format_debug_truncated_to_head_and_tail, format_debug_truncated_to_max_chars,
+    format_debug_truncated_to_max_length, format_debug_truncated_to_max_length_and_element_chars,
+    format_debug_truncated_with_options,
+};
+use crate::debug_truncation_options::TruncationOptions;
 use std::fmt::{Debug, Formatter};
 
 /// Write a slice of `Debug` to `Formatter`, truncating to the specified length.
+/// The caller's formatter flags, e.g. `{:#?}` or `{:x?}`, are applied to each item.
 pub fn format_debug_slice_truncated_to_max_length(
     f: &mut Formatter<'_>,
     debug_slice: &[impl Debug],
     max_length: usize,
 ) -> Result<(), std::fmt::Error> {
-    if max_length == 0 {
-        if debug_slice.is_empty() {
-            write!(f, "{:?}", debug_slice)
-        } else {
-            write!(f, "(length={})", debug_slice.len())
-        }
-    } else if debug_slice.len() <= max_length {
-        write!(f, "{:?}", debug_slice)
-    } else {
-        write!(
-            f,
-            "(length={} truncated to {}) {:?}...",
-            debug_slice.len(),
-            max_length,
-            &debug_slice[0..max_length]
-        )
-    }
+    format_debug_truncated_to_max_length(f, debug_slice, max_length)
 }
 
-/// Trait fixing the slice truncation length.
-trait TruncateSliceDebug<'slice, D: Debug> {
-    const MAX_LENGTH: usize;
+/// Write a slice of `Debug` to `Formatter`, truncating to the specified length
+/// and capping each item to `max_element_chars` chars, e.g. `["abc…"(+9812 chars), "d"]`.
+pub fn format_debug_slice_truncated_to_max_length_and_element_chars(
+    f: &mut Formatter<'_>,
+    debug_slice: &[impl Debug],
+    max_length: usize,
+    max_element_chars: usize,
+) -> Result<(), std::fmt::Error> {
+    format_debug_truncated_to_max_length_and_element_chars(
+        f,
+        debug_slice,
+        max_length,
+        max_element_chars,
+    )
+}
 
-    fn format_debug_truncated_to_max_length(
-        &self,
-        f: &mut Formatter<'_>,
-        debug_slice: &'slice [D],
-    ) -> Result<(), std::fmt::Error> {
-        format_debug_slice_truncated_to_max_length(f, debug_slice, Self::MAX_LENGTH)
-    }
+/// Write a slice of `Debug` to `Formatter`, truncating as specified by `options`.
+pub fn format_debug_slice_truncated_with_options(
+    f: &mut Formatter<'_>,
+    debug_slice: &[impl Debug],
+    options: &TruncationOptions,
+) -> Result<(), std::fmt::Error> {
+    format_debug_truncated_with_options(f, debug_slice, options)
 }
 
-/// Truncate slices to `MAX_LENGTH` declared in impl.
-pub struct TruncatedDebug<'slice, D: Debug> {
-    slice: &'slice [D],
+/// Write a slice of `Debug` to `Formatter`, showing as many whole items as fit within
+/// `max_total_chars` chars of item output, e.g. `(length=100, 7 shown within 2000 chars) [..]...`.
+pub fn format_debug_slice_truncated_to_max_chars(
+    f: &mut Formatter<'_>,
+    debug_slice: &[impl Debug],
+    max_total_chars: usize,
+) -> Result<(), std::fmt::Error> {
+    format_debug_truncated_to_max_chars(f, debug_slice, max_total_chars)
 }
 
-impl<'slice, D: Debug> TruncateSliceDebug<'slice, D> for TruncatedDebug<'slice, D> {
-    const MAX_LENGTH: usize = 25;
+/// Write a slice of `Debug + PartialEq` to `Formatter`, collapsing each run of consecutive
+/// equal items into one, e.g. `[0 ×4096, 7, 8]`, then truncating to `max_length` runs.
+pub fn format_debug_slice_run_length_truncated_to_max_length<D: Debug + PartialEq>(
+    f: &mut Formatter<'_>,
+    debug_slice: &[D],
+    max_length: usize,
+) -> Result<(), std::fmt::Error> {
+    format_debug_run_length_truncated_to_max_length(f, debug_slice, max_length)
 }
 
-impl<'slice, D: Debug> Debug for TruncatedDebug<'slice, D> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        self.format_debug_truncated_to_max_length(f, self.slice)
-    }
+/// Write a slice of `Debug + PartialEq` to `Formatter`, collapsing each run of consecutive
+/// equal items into one, then truncating as specified by `options`, which count runs.
+pub fn format_debug_slice_run_length_truncated_with_options<D: Debug + PartialEq>(
+    f: &mut Formatter<'_>,
+    debug_slice: &[D],
+    options: &TruncationOptions,
+) -> Result<(), std::fmt::Error> {
+    format_debug_run_length_truncated_with_options(f, debug_slice, options)
+}
+
+/// Write a slice of `Debug` to `Formatter`, showing at most `max_length` items evenly spaced
+/// over the whole slice, each with its index, e.g. `(length=10000 sampled 10 @stride 1000) [0: a, 1000: b, ..]...`.
+pub fn format_debug_slice_sampled_by_stride(
+    f: &mut Formatter<'_>,
+    debug_slice: &[impl Debug],
+    max_length: usize,
+) -> Result<(), std::fmt::Error> {
+    format_debug_sampled_by_stride(f, debug_slice, max_length)
+}
+
+/// Write a slice of `Debug` to `Formatter`, showing `max_length` items picked at random,
+/// the same every time for the same `seed`, in slice order and each with its index.
+pub fn format_debug_slice_sampled_by_reservoir(
+    f: &mut Formatter<'_>,
+    debug_slice: &[impl Debug],
+    max_length: usize,
+    seed: u64,
+) -> Result<(), std::fmt::Error> {
+    format_debug_sampled_by_reservoir(f, debug_slice, max_length, seed)
+}
+
+/// Write a slice of `Debug` to `Formatter`, showing only the first `head_length`
+/// and the last `tail_length` items.
+pub fn format_debug_slice_truncated_to_head_and_tail(
+    f: &mut Formatter<'_>,
+    debug_slice: &[impl Debug],
+    head_length: usize,
+    tail_length: usize,
+) -> Result<(), std::fmt::Error> {
+    format_debug_truncated_to_head_and_tail(f, debug_slice, head_length, tail_length)
 }
 
-#[allow(dead_code)]
+/// Arbitrary constant used to truncate number of items in slice debug string.
+pub const DEFAULT_SLICE_DEBUG_FMT_MAX_ITEMS: usize = 25;
+
+/// Truncate slices to `DEFAULT_SLICE_DEBUG_FMT_MAX_ITEMS`, or to the format precision if set.
+pub type TruncatedDebug<'slice, D> =
+    crate::debug_truncation::TruncatedDebug<&'slice [D], DEFAULT_SLICE_DEBUG_FMT_MAX_ITEMS>;
+
 /// Helper function to wrap a slice in a struct that will truncate its debug output.
+/// ```rust
+/// use strings::debug_slice_truncation::truncated_debug;
+/// let data = (1..=30).collect::<Vec<_>>();
+/// assert!(format!("{:?}", truncated_debug(&data)).starts_with("(length=30 truncated to 25) "));
+/// assert_eq!(
+///     format!("{:.3?}", truncated_debug(&data)),
+///     "(length=30 truncated to 3) [1, 2, 3]..."
+/// );
+/// ```
+#[allow(unknown_lints, mismatched_lifetime_syntaxes)]
 pub fn truncated_debug<D: Debug>(slice: &[D]) -> TruncatedDebug<D> {
-    TruncatedDebug { slice }
+    TruncatedDebug::new(slice)
 }
 
 #[cfg(test)]
 mod tests {
     mod test_truncated_slice {
-        use crate::debug_slice_truncation::TruncateSliceDebug;
+        use crate::debug_truncation::TruncatedDebug;
         use std::fmt::{Debug, Formatter};
         //        use linked_hash_set::LinkedHashSet;
 
-        /// Test struct truncating to MAX_LENGTH: 5
-        pub struct TestTruncatedDebug<'slice, D: Debug> {
-            slice: &'slice [D],
-        }
-
-        impl<'slice, D: Debug> TruncateSliceDebug<'slice, D> for TestTruncatedDebug<'slice, D> {
-            const MAX_LENGTH: usize = 5;
-        }
-
-        impl<'slice, D: Debug> Debug for TestTruncatedDebug<'slice, D> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-                self.format_debug_truncated_to_max_length(f, self.slice)
-            }
-        }
+        /// Test type truncating to MAX_LENGTH: 5
+        pub type TestTruncatedDebug<'slice, D> = TruncatedDebug<&'slice [D], 5>;
 
+        #[allow(unknown_lints, mismatched_lifetime_syntaxes)]
         pub fn test_truncated_debug<D: Debug>(slice: &[D]) -> TestTruncatedDebug<D> {
-            TestTruncatedDebug { slice }
+            TestTruncatedDebug::new(slice)
         }
 
         /// Example struct which has a struct field
@@ -102,6 +163,7 @@ mod tests {
             }
         }
 
+        #[allow(clippy::needless_borrow)]
         fn truncate_debug_string_for(inputs: &[i32]) -> String {
             let input = StructWithASlice::new(&inputs);
             let result = format!("{:?}", input);
@@ -132,6 +194,20 @@ mod tests {
 
             assert_eq!(result, "test_slice: [1, 2, 3, 4]");
         }
+
+        #[test]
+        fn truncated_slice_length_precision_overrides_max_length() {
+            let result = format!("{:.2?}", test_truncated_debug(&[1, 2, 3, 4]));
+
+            assert_eq!(result, "(length=4 truncated to 2) [1, 2]...");
+        }
+
+        #[test]
+        fn truncated_slice_length_precision_greater_than_max_length() {
+            let result = format!("{:.6?}", test_truncated_debug(&[1, 2, 3, 4, 5, 6]));
+
+            assert_eq!(result, "[1, 2, 3, 4, 5, 6]");
+        }
     }
 
     mod test_debug_slice_truncated_to_max_length {
@@ -152,9 +228,24 @@ mod tests {
             }
         }
 
-        use crate::debug_slice_truncation::format_debug_slice_truncated_to_max_length;
+        struct StructWithOptions<'slice, D: Debug> {
+            slice: &'slice [D],
+            options: TruncationOptions,
+        }
+
+        impl<'slice, D: Debug> Debug for StructWithOptions<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_slice_truncated_with_options(f, self.slice, &self.options)
+            }
+        }
+
+        use crate::debug_slice_truncation::{
+            format_debug_slice_truncated_to_max_length, format_debug_slice_truncated_with_options,
+        };
+        use crate::debug_truncation_options::TruncationOptions;
         use std::fmt::{Debug, Formatter};
 
+        #[allow(clippy::needless_borrow)]
         fn truncate_debug_string_for(inputs: &[i32], max_length: usize) -> String {
             let input = StructWithASlice::new(&inputs, max_length);
             let result = format!("{:?}", input);
@@ -190,6 +281,35 @@ mod tests {
             assert_eq!(result, "[]");
         }
 
+        #[test]
+        fn debug_slice_truncated_with_exact_size_preset_options() {
+            let inputs = (1..=50).collect::<Vec<_>>();
+            let input = StructWithASlice::new(&inputs, 42);
+            let expected = format!("{:?}", input);
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    slice: &inputs,
+                    options: TruncationOptions::exact_size_preset(),
+                }
+            );
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn debug_slice_truncated_with_options_without_header() {
+            let result = format!(
+                "{:?}",
+                StructWithOptions {
+                    slice: &[1, 2, 3],
+                    options: TruncationOptions::new().max_length(1).show_header(false),
+                }
+            );
+
+            assert_eq!(result, "[1]...");
+        }
+
         #[test]
         fn empty_debug_slice_truncated_to_max_length_zero() {
             let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 0);
@@ -197,4 +317,467 @@ mod tests {
             assert_eq!(result, "(length=5)");
         }
     }
+
+    mod test_debug_slice_truncated_to_max_chars {
+        use crate::debug_slice_truncation::format_debug_slice_truncated_to_max_chars;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithASlice<'slice, D: Debug> {
+            slice: &'slice [D],
+            max_total_chars: usize,
+        }
+
+        impl<'slice, D: Debug> Debug for StructWithASlice<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_slice_truncated_to_max_chars(f, self.slice, self.max_total_chars)
+            }
+        }
+
+        fn truncate_debug_string_for(inputs: &[&str], max_total_chars: usize) -> String {
+            let input = StructWithASlice {
+                slice: inputs,
+                max_total_chars,
+            };
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_chars_longer_than_max() {
+            let result = truncate_debug_string_for(&["abc", "defg", "hij"], 12);
+
+            assert_eq!(
+                result,
+                "(length=3, 2 shown within 12 chars) [\"abc\", \"defg\"]..."
+            );
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_chars_equal_max() {
+            let result = truncate_debug_string_for(&["abc", "defg"], 11);
+
+            assert_eq!(result, "[\"abc\", \"defg\"]");
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_chars_first_element_too_long() {
+            let result = truncate_debug_string_for(&["abcdefghij"], 5);
+
+            assert_eq!(result, "(length=1, 0 shown within 5 chars) []...");
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_chars_pretty() {
+            let input = StructWithASlice {
+                slice: &[1, 22, 333],
+                max_total_chars: 3,
+            };
+            let result = format!("{:#?}", input);
+
+            assert_eq!(
+                result,
+                "(length=3, 2 shown within 3 chars) [\n    1,\n    22,\n    ...,\n]"
+            );
+        }
+    }
+
+    mod test_debug_slice_truncated_to_head_and_tail {
+        use crate::debug_slice_truncation::format_debug_slice_truncated_to_head_and_tail;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithASlice<'slice, D: Debug> {
+            slice: &'slice [D],
+            head_length: usize,
+            tail_length: usize,
+        }
+
+        impl<'slice, D: Debug> Debug for StructWithASlice<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_slice_truncated_to_head_and_tail(
+                    f,
+                    self.slice,
+                    self.head_length,
+                    self.tail_length,
+                )
+            }
+        }
+
+        fn truncate_debug_string_for(
+            inputs: &[i32],
+            head_length: usize,
+            tail_length: usize,
+        ) -> String {
+            let input = StructWithASlice {
+                slice: inputs,
+                head_length,
+                tail_length,
+            };
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_head_and_tail_longer_than_max() {
+            let inputs = (1..=1000).collect::<Vec<_>>();
+            let result = truncate_debug_string_for(&inputs, 5, 3);
+
+            assert_eq!(
+                result,
+                "(length=1000 showing 5+3) [1, 2, 3, 4, 5, ..., 998, 999, 1000]"
+            );
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_tail_only() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 0, 2);
+
+            assert_eq!(result, "(length=5 showing 0+2) [..., 4, 5]");
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_head_and_tail_equal_max() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 3, 2);
+
+            assert_eq!(result, "[1, 2, 3, 4, 5]");
+        }
+
+        #[test]
+        fn empty_debug_slice_truncated_to_head_and_tail_zero() {
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], 0, 0);
+
+            assert_eq!(result, "(length=5)");
+        }
+    }
+
+    mod test_debug_slice_truncated_formatter_flags {
+        use crate::debug_slice_truncation::{
+            format_debug_slice_truncated_to_head_and_tail,
+            format_debug_slice_truncated_to_max_length,
+        };
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithASlice<'slice, D: Debug> {
+            slice: &'slice [D],
+            max_length: usize,
+        }
+
+        impl<'slice, D: Debug> Debug for StructWithASlice<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_slice_truncated_to_max_length(f, self.slice, self.max_length)
+            }
+        }
+
+        struct StructWithAHeadAndTailSlice<'slice, D: Debug> {
+            slice: &'slice [D],
+        }
+
+        impl<'slice, D: Debug> Debug for StructWithAHeadAndTailSlice<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_slice_truncated_to_head_and_tail(f, self.slice, 1, 1)
+            }
+        }
+
+        #[test]
+        fn debug_slice_truncated_alternate() {
+            let input = StructWithASlice {
+                slice: &[1, 2, 3],
+                max_length: 2,
+            };
+            let result = format!("{:#?}", input);
+
+            assert_eq!(
+                result,
+                "(length=3 truncated to 2) [\n    1,\n    2,\n    ...,\n]"
+            );
+        }
+
+        #[test]
+        fn debug_slice_not_truncated_alternate() {
+            let input = StructWithASlice {
+                slice: &[1, 2],
+                max_length: 2,
+            };
+            let result = format!("{:#?}", input);
+
+            assert_eq!(result, format!("{:#?}", [1, 2]));
+        }
+
+        #[test]
+        fn debug_slice_truncated_lower_hex() {
+            let input = StructWithASlice {
+                slice: &[10, 11, 255],
+                max_length: 2,
+            };
+            let result = format!("{:x?}", input);
+
+            assert_eq!(result, "(length=3 truncated to 2) [a, b]...");
+        }
+
+        #[test]
+        fn debug_slice_truncated_upper_hex() {
+            let input = StructWithASlice {
+                slice: &[10, 11, 255],
+                max_length: 2,
+            };
+            let result = format!("{:X?}", input);
+
+            assert_eq!(result, "(length=3 truncated to 2) [A, B]...");
+        }
+
+        #[test]
+        fn debug_slice_not_truncated_upper_hex() {
+            let input = StructWithASlice {
+                slice: &[10, 11, 255],
+                max_length: 3,
+            };
+            let result = format!("{:X?}", input);
+
+            assert_eq!(result, "[A, B, FF]");
+        }
+
+        #[test]
+        fn debug_slice_truncated_width() {
+            let input = StructWithASlice {
+                slice: &[1, 2, 3],
+                max_length: 2,
+            };
+            let result = format!("{:3?}", input);
+
+            assert_eq!(result, "(length=3 truncated to 2) [  1,   2]...");
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_head_and_tail_alternate_hex() {
+            let input = StructWithAHeadAndTailSlice {
+                slice: &[10, 11, 12],
+            };
+            let result = format!("{:#x?}", input);
+
+            assert_eq!(
+                result,
+                "(length=3 showing 1+1) [\n    0xa,\n    ...,\n    0xc,\n]"
+            );
+        }
+    }
+
+    mod test_debug_slice_truncated_to_max_length_and_element_chars {
+        use crate::debug_slice_truncation::format_debug_slice_truncated_to_max_length_and_element_chars;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithASlice<'slice, D: Debug> {
+            slice: &'slice [D],
+            max_length: usize,
+            max_element_chars: usize,
+        }
+
+        impl<'slice, D: Debug> Debug for StructWithASlice<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_slice_truncated_to_max_length_and_element_chars(
+                    f,
+                    self.slice,
+                    self.max_length,
+                    self.max_element_chars,
+                )
+            }
+        }
+
+        fn truncate_debug_string_for<D: Debug>(
+            inputs: &[D],
+            max_length: usize,
+            max_element_chars: usize,
+        ) -> String {
+            let input = StructWithASlice {
+                slice: inputs,
+                max_length,
+                max_element_chars,
+            };
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_length_and_element_chars_strings() {
+            let blob = "abc".repeat(1000);
+            let inputs = [blob.as_str(), "d", "e"];
+            let result = truncate_debug_string_for(&inputs, 2, 4);
+
+            assert_eq!(
+                result,
+                "(length=3 truncated to 2) [\"abc…\"(+2997 chars), \"d\"]..."
+            );
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_length_and_element_chars_numbers() {
+            let result = truncate_debug_string_for(&[1, 12345, 123], 5, 3);
+
+            assert_eq!(result, "[1, 123…(+2 chars), 123]");
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_length_and_element_chars_closing_quote_only() {
+            let result = truncate_debug_string_for(&["abc"], 5, 4);
+
+            assert_eq!(result, "[\"abc\"]");
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_length_and_element_chars_nested_string() {
+            let result = truncate_debug_string_for(&[Some("abcdef")], 5, 8);
+
+            assert_eq!(result, "[Some(\"ab…\"(+5 chars)]");
+        }
+
+        #[test]
+        fn debug_slice_truncated_to_max_length_and_element_chars_escaped_quote() {
+            let result = truncate_debug_string_for(&["a\"bcdef"], 5, 4);
+
+            assert_eq!(result, "[\"a\\\"…\"(+5 chars)]");
+        }
+    }
+
+    mod test_debug_slice_sampled {
+        use crate::debug_slice_truncation::{
+            format_debug_slice_sampled_by_reservoir, format_debug_slice_sampled_by_stride,
+        };
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithASlice<'slice, D: Debug> {
+            slice: &'slice [D],
+            max_length: usize,
+            seed: Option<u64>,
+        }
+
+        impl<'slice, D: Debug> Debug for StructWithASlice<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                match self.seed {
+                    Some(seed) => format_debug_slice_sampled_by_reservoir(
+                        f,
+                        self.slice,
+                        self.max_length,
+                        seed,
+                    ),
+                    None => format_debug_slice_sampled_by_stride(f, self.slice, self.max_length),
+                }
+            }
+        }
+
+        fn truncate_debug_string_for(
+            inputs: &[&str],
+            max_length: usize,
+            seed: Option<u64>,
+        ) -> String {
+            let input = StructWithASlice {
+                slice: inputs,
+                max_length,
+                seed,
+            };
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn debug_slice_sampled_by_stride() {
+            let result = truncate_debug_string_for(&["a", "b", "c", "d", "e"], 2, None);
+
+            assert_eq!(
+                result,
+                "(length=5 sampled 2 @stride ~2) [0: \"a\", 2: \"c\"]..."
+            );
+        }
+
+        #[test]
+        fn debug_slice_sampled_by_reservoir() {
+            let result = truncate_debug_string_for(&["a", "b", "c", "d", "e"], 2, Some(1));
+
+            assert_eq!(
+                result,
+                "(length=5 sampled 2 @seed 1) [1: \"b\", 4: \"e\"]..."
+            );
+            assert_eq!(
+                result,
+                truncate_debug_string_for(&["a", "b", "c", "d", "e"], 2, Some(1))
+            );
+        }
+    }
+
+    mod test_debug_slice_run_length_truncated {
+        use crate::debug_slice_truncation::{
+            format_debug_slice_run_length_truncated_to_max_length,
+            format_debug_slice_run_length_truncated_with_options,
+        };
+        use crate::debug_truncation_options::TruncationOptions;
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithASlice<'slice, D: Debug> {
+            slice: &'slice [D],
+            max_length: usize,
+        }
+
+        impl<'slice, D: Debug + PartialEq> Debug for StructWithASlice<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_slice_run_length_truncated_to_max_length(
+                    f,
+                    self.slice,
+                    self.max_length,
+                )
+            }
+        }
+
+        struct StructWithOptions<'slice, D: Debug> {
+            slice: &'slice [D],
+            options: TruncationOptions,
+        }
+
+        impl<'slice, D: Debug + PartialEq> Debug for StructWithOptions<'slice, D> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_slice_run_length_truncated_with_options(f, self.slice, &self.options)
+            }
+        }
+
+        fn truncate_debug_string_for(inputs: &[&str], max_length: usize) -> String {
+            let input = StructWithASlice {
+                slice: inputs,
+                max_length,
+            };
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn debug_slice_run_length_truncated_longer_than_max_length() {
+            let result = truncate_debug_string_for(&["pad", "pad", "a", "pad"], 2);
+
+            assert_eq!(
+                result,
+                "(length=4 in 3 runs truncated to 2) [\"pad\" ×2, \"a\"]..."
+            );
+        }
+
+        #[test]
+        fn debug_slice_run_length_truncated_without_runs() {
+            let result = truncate_debug_string_for(&["a", "b"], 2);
+
+            assert_eq!(result, "[\"a\", \"b\"]");
+        }
+
+        #[test]
+        fn debug_slice_run_length_truncated_within_max_chars() {
+            let input = StructWithOptions {
+                slice: &[0, 0, 0, 123456],
+                options: TruncationOptions::new().max_total_chars(4),
+            };
+            let result = format!("{:?}", input);
+
+            assert_eq!(
+                result,
+                "(length=4 in 2 runs, 1 shown within 4 chars) [0 ×3]..."
+            );
+        }
+    }
 }
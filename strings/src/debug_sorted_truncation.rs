@@ -0,0 +1,697 @@
+use crate::debug_truncation::{
+    format_debug_shaped_truncated_with_options, ContainerShape, MapEntry,
+    DEFAULT_DEBUG_FMT_MAX_ITEMS,
+};
+use crate::debug_truncation_options::TruncationOptions;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Ord + Debug` to `Formatter` using the
+/// delimiters of `shape`, showing the smallest `options.head_length` items in order,
+/// followed by the largest `options.tail_length` items if a tail is shown.
+/// They are picked with heaps of at most that many items, in O(len · log N) time.
+/// The items shown are picked by order, so `options.sampling` is ignored.
+pub fn format_debug_shaped_sorted_truncated_with_options<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    options: &TruncationOptions,
+    shape: ContainerShape,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug + Ord,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let exact_iter = exact_into_iter.into_iter();
+    let length = exact_iter.len();
+    let (smallest, largest) = smallest_and_largest_sorted(exact_iter, options);
+    format_debug_selected_truncated(f, smallest, largest, length, options, shape)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Hash + Debug` to `Formatter` using the
+/// delimiters of `shape`, showing the items with the smallest and largest hashes,
+/// as `format_debug_shaped_sorted_truncated_with_options` does for the items themselves,
+/// using a hash which is the same in every run and on every platform, for items which are not `Ord`.
+/// Items with equal hashes, which is rare, may be shown in either order.
+pub fn format_debug_shaped_hash_ordered_truncated_with_options<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    options: &TruncationOptions,
+    shape: ContainerShape,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug + Hash,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let exact_iter = exact_into_iter.into_iter();
+    let length = exact_iter.len();
+    let (smallest, largest) = smallest_and_largest_sorted(
+        exact_iter.map(|element| OrderedBy::new(stable_hash(&element), element)),
+        options,
+    );
+    let item_of = |entry: OrderedBy<u64, D>| entry.item;
+    let smallest = smallest.into_iter().map(item_of).collect();
+    let largest = largest.into_iter().map(item_of).collect();
+    format_debug_selected_truncated(f, smallest, largest, length, options, shape)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `(Ord + Debug, Debug)` to `Formatter`
+/// as a map, showing the entries with the smallest and largest keys in order,
+/// as `format_debug_shaped_sorted_truncated_with_options` does for items.
+pub fn format_debug_map_sorted_truncated_with_options<K, V, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    options: &TruncationOptions,
+) -> Result<(), std::fmt::Error>
+where
+    K: Debug + Ord,
+    V: Debug,
+    ESI: ExactSizeIterator<Item = (K, V)>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let exact_iter = exact_into_iter.into_iter();
+    let length = exact_iter.len();
+    let (smallest, largest) = smallest_and_largest_sorted(
+        exact_iter.map(|(key, value)| OrderedBy::new(key, value)),
+        options,
+    );
+    let entry_of = |entry: OrderedBy<K, V>| MapEntry(entry.order, entry.item);
+    let smallest = smallest.into_iter().map(entry_of).collect();
+    let largest = largest.into_iter().map(entry_of).collect();
+    format_debug_selected_truncated(f, smallest, largest, length, options, ContainerShape::Set)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `(Hash + Debug, Debug)` to `Formatter`
+/// as a map, showing the entries with the smallest and largest hashes of their keys,
+/// as `format_debug_shaped_hash_ordered_truncated_with_options` does for items.
+pub fn format_debug_map_hash_ordered_truncated_with_options<K, V, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    options: &TruncationOptions,
+) -> Result<(), std::fmt::Error>
+where
+    K: Debug + Hash,
+    V: Debug,
+    ESI: ExactSizeIterator<Item = (K, V)>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let exact_iter = exact_into_iter.into_iter();
+    let length = exact_iter.len();
+    let (smallest, largest) = smallest_and_largest_sorted(
+        exact_iter.map(|(key, value)| OrderedBy::new(stable_hash(&key), (key, value))),
+        options,
+    );
+    let entry_of = |entry: OrderedBy<u64, (K, V)>| MapEntry(entry.item.0, entry.item.1);
+    let smallest = smallest.into_iter().map(entry_of).collect();
+    let largest = largest.into_iter().map(entry_of).collect();
+    format_debug_selected_truncated(f, smallest, largest, length, options, ContainerShape::Set)
+}
+
+/// The smallest `max_length` items, sorted, keeping at most `max_length` at a time,
+/// along with how many items there were.
+pub(crate) fn smallest_sorted<T: Ord>(
+    items: impl Iterator<Item = T>,
+    max_length: usize,
+) -> (Vec<T>, usize) {
+    let mut heap = BinaryHeap::with_capacity(max_length.min(items.size_hint().0));
+    let mut length = 0;
+    for item in items {
+        length += 1;
+        if heap.len() < max_length {
+            heap.push(item);
+        } else if let Some(mut largest) = heap.peek_mut() {
+            if item < *largest {
+                *largest = item;
+            }
+        }
+    }
+    (heap.into_sorted_vec(), length)
+}
+
+/// The smallest `options.head_length` items and, if a tail is shown, the largest `options.tail_length`
+/// of the rest, each sorted, keeping at most that many at a time.
+/// An item leaving the smallest is one of the rest, so no item is picked twice.
+fn smallest_and_largest_sorted<T: Ord>(
+    items: impl Iterator<Item = T>,
+    options: &TruncationOptions,
+) -> (Vec<T>, Vec<T>) {
+    let head_length = options.head_length;
+    let tail_length = match options.shows_tail() {
+        true => options.tail_length,
+        false => 0,
+    };
+    let mut smallest = BinaryHeap::with_capacity(head_length.min(items.size_hint().0));
+    let mut largest = BinaryHeap::with_capacity(tail_length.min(items.size_hint().0));
+    for item in items {
+        if smallest.len() < head_length {
+            smallest.push(item);
+            continue;
+        }
+        let rest = match smallest.peek_mut() {
+            Some(mut most) if item < *most => std::mem::replace(&mut *most, item),
+            _ => item,
+        };
+        if largest.len() < tail_length {
+            largest.push(Reverse(rest));
+        } else if let Some(mut least) = largest.peek_mut() {
+            if rest > least.0 {
+                *least = Reverse(rest);
+            }
+        }
+    }
+    let largest = largest
+        .into_sorted_vec()
+        .into_iter()
+        .rev()
+        .map(|Reverse(item)| item)
+        .collect();
+    (smallest.into_sorted_vec(), largest)
+}
+
+/// Write the `smallest` and `largest` items out of `length`, with the header and marker `options` would write
+/// for the first `smallest.len()` and last `largest.len()` items.
+fn format_debug_selected_truncated<D: Debug>(
+    f: &mut Formatter<'_>,
+    smallest: Vec<D>,
+    largest: Vec<D>,
+    length: usize,
+    options: &TruncationOptions,
+    shape: ContainerShape,
+) -> Result<(), std::fmt::Error> {
+    let mut options = options.clone();
+    options.sampling = None;
+    let not_shown_length = length - smallest.len() - largest.len();
+    let entries = SelectedEntries {
+        smallest: smallest.into_iter(),
+        not_shown_length,
+        largest: largest.into_iter(),
+    };
+    format_debug_shaped_truncated_with_options(f, entries, &options, shape)
+}
+
+/// Selected items with placeholders for the rest between them, so the length reported matches the source.
+/// The placeholders are where the items not shown would be, so are never formatted.
+struct SelectedEntries<D> {
+    smallest: std::vec::IntoIter<D>,
+    not_shown_length: usize,
+    largest: std::vec::IntoIter<D>,
+}
+
+impl<D> Iterator for SelectedEntries<D> {
+    type Item = SelectedEntry<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.smallest.next() {
+            Some(item) => Some(SelectedEntry::Shown(item)),
+            None if self.not_shown_length > 0 => {
+                self.not_shown_length -= 1;
+                Some(SelectedEntry::NotShown)
+            }
+            None => self.largest.next().map(SelectedEntry::Shown),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let length = self.smallest.len() + self.not_shown_length + self.largest.len();
+        (length, Some(length))
+    }
+}
+
+impl<D> ExactSizeIterator for SelectedEntries<D> {}
+
+enum SelectedEntry<D> {
+    Shown(D),
+    NotShown,
+}
+
+impl<D: Debug> Debug for SelectedEntry<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            SelectedEntry::Shown(item) => item.fmt(f),
+            SelectedEntry::NotShown => Ok(()),
+        }
+    }
+}
+
+/// `item` compared only by `order`.
+pub(crate) struct OrderedBy<O, D> {
+    pub(crate) order: O,
+    pub(crate) item: D,
+}
+
+impl<O, D> OrderedBy<O, D> {
+    pub(crate) fn new(order: O, item: D) -> Self {
+        Self { order, item }
+    }
+}
+
+impl<O: Ord, D> PartialEq for OrderedBy<O, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.order == other.order
+    }
+}
+
+impl<O: Ord, D> Eq for OrderedBy<O, D> {}
+
+impl<O: Ord, D> PartialOrd for OrderedBy<O, D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<O: Ord, D> Ord for OrderedBy<O, D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order.cmp(&other.order)
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the randomly seeded `HashMap` hasher gives the same hash in every run.
+/// Integers are fed as little-endian bytes, and `usize` as 64 bits, so the hash is the same on every platform.
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64)
+    }
+}
+
+fn stable_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StableHasher(0xcbf2_9ce4_8422_2325);
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Unordered collections which can be truncated to their smallest items, shown in order.
+pub trait SortedTruncateDebug {
+    fn format_debug_sorted_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error>;
+}
+
+impl<D: Debug + Ord, S> SortedTruncateDebug for HashSet<D, S> {
+    fn format_debug_sorted_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        format_debug_shaped_sorted_truncated_with_options(f, self, options, ContainerShape::Set)
+    }
+}
+
+impl<K: Debug + Ord, V: Debug, S> SortedTruncateDebug for HashMap<K, V, S> {
+    fn format_debug_sorted_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        format_debug_map_sorted_truncated_with_options(f, self, options)
+    }
+}
+
+impl<C: SortedTruncateDebug + ?Sized> SortedTruncateDebug for &C {
+    fn format_debug_sorted_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        (**self).format_debug_sorted_truncated_with_options(f, options)
+    }
+}
+
+/// Unordered collections which can be truncated to items picked and ordered by a stable hash.
+pub trait HashOrderedTruncateDebug {
+    fn format_debug_hash_ordered_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error>;
+}
+
+impl<D: Debug + Hash, S> HashOrderedTruncateDebug for HashSet<D, S> {
+    fn format_debug_hash_ordered_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        format_debug_shaped_hash_ordered_truncated_with_options(
+            f,
+            self,
+            options,
+            ContainerShape::Set,
+        )
+    }
+}
+
+impl<K: Debug + Hash, V: Debug, S> HashOrderedTruncateDebug for HashMap<K, V, S> {
+    fn format_debug_hash_ordered_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        format_debug_map_hash_ordered_truncated_with_options(f, self, options)
+    }
+}
+
+impl<C: HashOrderedTruncateDebug + ?Sized> HashOrderedTruncateDebug for &C {
+    fn format_debug_hash_ordered_truncated_with_options(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+    ) -> Result<(), std::fmt::Error> {
+        (**self).format_debug_hash_ordered_truncated_with_options(f, options)
+    }
+}
+
+/// Truncate a borrowed unordered collection to its smallest `SIZE` items, or as many as the
+/// format precision if set, shown in order.
+pub struct SortedHolder<'collection, C: ?Sized, const SIZE: usize> {
+    collection: &'collection C,
+    options: TruncationOptions,
+}
+
+impl<'collection, C: ?Sized, const SIZE: usize> SortedHolder<'collection, C, SIZE> {
+    pub fn new(collection: &'collection C) -> Self {
+        Self::with_options(collection, TruncationOptions::new().max_length(SIZE))
+    }
+
+    /// Truncate as specified by `options` rather than to `SIZE`.
+    pub fn with_options(collection: &'collection C, options: TruncationOptions) -> Self {
+        Self {
+            collection,
+            options,
+        }
+    }
+}
+
+impl<'collection, C, const SIZE: usize> Debug for SortedHolder<'collection, C, SIZE>
+where
+    C: SortedTruncateDebug + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        self.collection
+            .format_debug_sorted_truncated_with_options(f, &options)
+    }
+}
+
+/// Truncate a borrowed unordered collection to `SIZE` items, or as many as the format precision
+/// if set, picked and ordered by a hash which is the same in every run.
+pub struct HashOrderedHolder<'collection, C: ?Sized, const SIZE: usize> {
+    collection: &'collection C,
+    options: TruncationOptions,
+}
+
+impl<'collection, C: ?Sized, const SIZE: usize> HashOrderedHolder<'collection, C, SIZE> {
+    pub fn new(collection: &'collection C) -> Self {
+        Self::with_options(collection, TruncationOptions::new().max_length(SIZE))
+    }
+
+    /// Truncate as specified by `options` rather than to `SIZE`.
+    pub fn with_options(collection: &'collection C, options: TruncationOptions) -> Self {
+        Self {
+            collection,
+            options,
+        }
+    }
+}
+
+impl<'collection, C, const SIZE: usize> Debug for HashOrderedHolder<'collection, C, SIZE>
+where
+    C: HashOrderedTruncateDebug + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        self.collection
+            .format_debug_hash_ordered_truncated_with_options(f, &options)
+    }
+}
+
+/// Truncates a `HashSet` or `HashMap` debug string to its smallest `DEFAULT_DEBUG_FMT_MAX_ITEMS`
+/// items, or keys, shown in order, so the output is the same in every run.
+/// ```rust
+/// use std::collections::HashSet;
+/// use strings::debug_sorted_truncation::truncated_sorted_debug_fmt;
+/// let data = (1..=50).rev().collect::<HashSet<_>>();
+/// assert_eq!(
+///     format!("{:.3?}", truncated_sorted_debug_fmt(&data)),
+///     "(length=50 truncated to 3) {1, 2, 3}..."
+/// );
+/// ```
+pub fn truncated_sorted_debug_fmt<C: ?Sized>(
+    collection: &C,
+) -> SortedHolder<'_, C, DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+    SortedHolder::new(collection)
+}
+
+/// Truncates a `HashSet` or `HashMap` debug string to `DEFAULT_DEBUG_FMT_MAX_ITEMS` items,
+/// picked and ordered by a hash of each item, or key, which is the same in every run.
+pub fn truncated_hash_ordered_debug_fmt<C: ?Sized>(
+    collection: &C,
+) -> HashOrderedHolder<'_, C, DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+    HashOrderedHolder::new(collection)
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_debug_sorted_truncated {
+        use super::super::{SortedHolder, SortedTruncateDebug};
+        use crate::debug_truncation_options::{Sampling, TruncationOptions};
+        use std::collections::{HashMap, HashSet};
+
+        fn truncate_debug_string_for<C: SortedTruncateDebug>(
+            collection: C,
+            max_length: usize,
+        ) -> String {
+            let options = TruncationOptions::new().max_length(max_length);
+            format!(
+                "{:?}",
+                SortedHolder::<_, 0>::with_options(&collection, options)
+            )
+        }
+
+        #[test]
+        fn debug_sorted_hash_set_longer_than_max_length() {
+            let hash_set = (1..=100).rev().collect::<HashSet<_>>();
+            let result = truncate_debug_string_for(hash_set, 3);
+
+            assert_eq!(result, "(length=100 truncated to 3) {1, 2, 3}...");
+        }
+
+        #[test]
+        fn debug_sorted_hash_set_shorter_than_max_length() {
+            let hash_set = vec![3, 1, 2].into_iter().collect::<HashSet<_>>();
+            let result = truncate_debug_string_for(hash_set, 5);
+
+            assert_eq!(result, "{1, 2, 3}");
+        }
+
+        #[test]
+        fn debug_sorted_hash_set_zero_length() {
+            let hash_set = (1..=3).collect::<HashSet<_>>();
+            let result = truncate_debug_string_for(hash_set, 0);
+
+            assert_eq!(result, "(length=3)");
+        }
+
+        #[test]
+        fn debug_sorted_hash_map_by_key() {
+            let hash_map = (1..=10).map(|i| (i, -i)).collect::<HashMap<_, _>>();
+            let result = truncate_debug_string_for(&hash_map, 2);
+
+            assert_eq!(result, "(length=10 truncated to 2) {1: -1, 2: -2}...");
+        }
+
+        #[test]
+        fn debug_sorted_holder_precision_and_pretty() {
+            let hash_set = (1..=10).collect::<HashSet<_>>();
+            let result = format!("{:#.2?}", SortedHolder::<_, 5>::new(&hash_set));
+
+            assert_eq!(
+                result,
+                "(length=10 truncated to 2) {\n    1,\n    2,\n    ...,\n}"
+            );
+        }
+
+        #[test]
+        fn debug_sorted_with_head_and_tail_shows_smallest_and_largest() {
+            let hash_set = (1..=10).rev().collect::<HashSet<_>>();
+            let options = TruncationOptions::new().head_and_tail(2, 1);
+            let result = format!(
+                "{:?}",
+                SortedHolder::<_, 0>::with_options(&hash_set, options)
+            );
+
+            assert_eq!(result, "(length=10 showing 2+1) {1, 2, ..., 10}");
+        }
+
+        #[test]
+        fn debug_sorted_with_head_and_tail_longer_than_length() {
+            let hash_set = vec![3, 1, 2].into_iter().collect::<HashSet<_>>();
+            let options = TruncationOptions::new().head_and_tail(2, 2);
+            let result = format!(
+                "{:?}",
+                SortedHolder::<_, 0>::with_options(&hash_set, options)
+            );
+
+            assert_eq!(result, "{1, 2, 3}");
+        }
+
+        #[test]
+        fn debug_sorted_ignores_sampling() {
+            let hash_set = (1..=10).collect::<HashSet<_>>();
+            let options = TruncationOptions::new()
+                .max_length(2)
+                .sampling(Sampling::Stride);
+            let result = format!(
+                "{:?}",
+                SortedHolder::<_, 0>::with_options(&hash_set, options)
+            );
+
+            assert_eq!(result, "(length=10 truncated to 2) {1, 2}...");
+        }
+
+        #[test]
+        fn debug_sorted_with_max_total_chars() {
+            let hash_set = vec![100, 1, 20].into_iter().collect::<HashSet<_>>();
+            let options = TruncationOptions::new().max_total_chars(3);
+            let result = format!(
+                "{:?}",
+                SortedHolder::<_, 0>::with_options(&hash_set, options)
+            );
+
+            assert_eq!(result, "(length=3, 2 shown within 3 chars) {1, 20}...");
+        }
+    }
+
+    mod test_debug_hash_ordered_truncated {
+        use super::super::{stable_hash, truncated_hash_ordered_debug_fmt, HashOrderedHolder};
+        use crate::debug_truncation_options::TruncationOptions;
+        use std::collections::hash_map::RandomState;
+        use std::collections::{HashMap, HashSet};
+
+        /// Example item which can be hashed but not ordered.
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Unordered(&'static str);
+
+        fn unordered_hash_set_of(items: &[&'static str]) -> HashSet<Unordered> {
+            let mut hash_set = HashSet::with_hasher(RandomState::new());
+            hash_set.extend(items.iter().map(|item| Unordered(item)));
+            hash_set
+        }
+
+        #[test]
+        fn debug_hash_ordered_is_the_same_for_any_iteration_order() {
+            let items = ["a", "b", "c", "d", "e", "f", "g", "h"];
+            let mut reversed = items;
+            reversed.reverse();
+
+            let first = format!(
+                "{:.3?}",
+                truncated_hash_ordered_debug_fmt(&unordered_hash_set_of(&items))
+            );
+            let second = format!(
+                "{:.3?}",
+                truncated_hash_ordered_debug_fmt(&unordered_hash_set_of(&reversed))
+            );
+
+            assert_eq!(first, second);
+        }
+
+        #[test]
+        fn debug_hash_ordered_picks_smallest_hashes() {
+            let items = ["a", "b", "c", "d", "e", "f", "g", "h"];
+            let result = format!(
+                "{:.2?}",
+                truncated_hash_ordered_debug_fmt(&unordered_hash_set_of(&items))
+            );
+
+            assert_eq!(
+                result,
+                "(length=8 truncated to 2) {Unordered(\"e\"), Unordered(\"d\")}..."
+            );
+        }
+
+        #[test]
+        fn debug_hash_ordered_head_and_tail() {
+            let items = ["a", "b", "c", "d", "e", "f", "g", "h"];
+            let options = TruncationOptions::new().head_and_tail(1, 1);
+            let result = format!(
+                "{:?}",
+                HashOrderedHolder::<_, 0>::with_options(&unordered_hash_set_of(&items), options)
+            );
+
+            assert_eq!(
+                result,
+                "(length=8 showing 1+1) {Unordered(\"e\"), ..., Unordered(\"h\")}"
+            );
+        }
+
+        #[test]
+        fn stable_hash_of_integers_is_little_endian() {
+            assert_eq!(stable_hash(&1u32), 0xad2a_ca77_4798_5764);
+            assert_eq!(stable_hash(&1usize), stable_hash(&1u64));
+        }
+
+        #[test]
+        fn debug_hash_ordered_hash_map_by_key() {
+            let hash_map = vec![(Unordered("a"), 1)]
+                .into_iter()
+                .collect::<HashMap<_, _>>();
+            let result = format!("{:?}", truncated_hash_ordered_debug_fmt(&hash_map));
+
+            assert_eq!(result, "{Unordered(\"a\"): 1}");
+        }
+    }
+}
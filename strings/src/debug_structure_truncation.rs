@@ -0,0 +1,640 @@
+use crate::debug_truncation_options::{ReportedLength, TruncationOptions};
+use std::fmt::{Debug, Formatter, Write};
+
+/// Written inside delimiters nested deeper than the maximum depth, e.g. `[…]`.
+pub const COLLAPSED_MARKER: &str = "…";
+
+/// String or char literal being passed through, so delimiters inside it are not counted.
+struct Literal {
+    quote: char,
+    escaped: bool,
+    /// Chars since the opening quote.
+    length: usize,
+}
+
+/// How the items of a delimited sequence are written.
+#[derive(PartialEq)]
+enum FrameKind {
+    /// `[...]`, or `{...}` not preceded by a name: a list, set or map, whose items are counted and truncated.
+    Collection,
+    /// `(...)`, or `{...}` preceded by a name, e.g. `Foo { .. }`: a tuple or struct, whose fields are all written.
+    Fields,
+    /// Nested deeper than the maximum depth, written as e.g. `[…]`.
+    Collapsed,
+}
+
+/// Delimited sequence which has been opened but not yet closed.
+struct Frame {
+    kind: FrameKind,
+    /// Offset of the opening delimiter in everything written, where the `(length=...)` header goes.
+    start: usize,
+    items: usize,
+    in_item: bool,
+    /// Separator and whitespace since the last item of a collection, written only once the next item is shown,
+    /// or the whitespace since the last hidden token, written before the closing delimiter.
+    pending_separator: String,
+    /// Whitespace before the first hidden item, once the items exceed the maximum length.
+    hidden_item_whitespace: Option<String>,
+    header_written: bool,
+}
+
+impl Frame {
+    fn new(kind: FrameKind, start: usize) -> Self {
+        Self {
+            kind,
+            start,
+            items: 0,
+            in_item: false,
+            pending_separator: String::new(),
+            hidden_item_whitespace: None,
+            header_written: false,
+        }
+    }
+
+    fn is_hidden(&self) -> bool {
+        self.hidden_item_whitespace.is_some()
+    }
+
+    fn is_suppressed(&self) -> bool {
+        self.kind == FrameKind::Collapsed || self.is_hidden()
+    }
+
+    /// Whether the output from the opening delimiter on is held back, as the header is not yet written.
+    fn is_held(&self) -> bool {
+        self.kind == FrameKind::Collection && !self.header_written
+    }
+}
+
+/// `fmt::Write` adapter which tokenizes the `Debug` output written to it, collapsing `[]`, `{}` and `()`
+/// nested deeper than `max_depth` to e.g. `[…]`, and truncating lists, sets and maps
+/// as `format_debug_slice_truncated_to_max_length` does, e.g. `(length=5 truncated to 2) [1, 2]...`.
+/// Struct and tuple fields, e.g. `Foo { a: 1, b: 2 }` or `Some(1)`, are never truncated.
+/// String and char literals are passed through untouched, and `{:#?}` output is truncated as
+/// `{:#?}` is elsewhere in this crate.
+///
+/// Output is passed on at the end of each write, except for a collection which is held back
+/// until it closes or has more items than the maximum length, as its header precedes it.
+/// A collection cut before it closes reports the items seen so far, e.g. `(length≥3 truncated to 2)`,
+/// while one nested in a collection still held back reports its exact length.
+/// Call `finish` once everything has been written.
+pub struct StructureTruncatingWriter<W> {
+    inner: W,
+    max_depth: usize,
+    options: TruncationOptions,
+    /// Output not yet passed on, which starts at offset `passed_on` of everything written.
+    output: String,
+    passed_on: usize,
+    frames: Vec<Frame>,
+    suppressed_nesting: usize,
+    literal: Option<Literal>,
+    previous: Option<char>,
+    /// Last char written other than whitespace, which is part of a name if a `{` opens a struct.
+    previous_token: Option<char>,
+}
+
+impl<W: Write> StructureTruncatingWriter<W> {
+    pub fn new(inner: W, max_depth: usize, max_length: usize) -> Self {
+        let options = TruncationOptions::new().max_length(max_length);
+        Self::with_options(inner, max_depth, options)
+    }
+
+    /// Truncate collections to the head length of `options`, using its marker and header setting.
+    /// Items are always taken from the front, so any tail length is ignored.
+    pub fn with_options(inner: W, max_depth: usize, options: TruncationOptions) -> Self {
+        let max_length = options.head_length;
+        Self {
+            inner,
+            max_depth,
+            options: options.max_length(max_length),
+            output: String::new(),
+            passed_on: 0,
+            frames: Vec::new(),
+            suppressed_nesting: 0,
+            literal: None,
+            previous: None,
+            previous_token: None,
+        }
+    }
+
+    /// Pass on whatever is held back, leaving unbalanced delimiters as written, and return `inner`.
+    pub fn finish(mut self) -> Result<W, std::fmt::Error> {
+        while let Some(frame) = self.frames.pop() {
+            if frame.kind != FrameKind::Collection {
+                continue;
+            }
+            if frame.is_hidden() && !frame.header_written {
+                self.insert_header(frame.start, ReportedLength::AtLeast(frame.items));
+            } else if !frame.is_hidden() {
+                self.output.push_str(&frame.pending_separator);
+            }
+        }
+        self.inner.write_str(&self.output)?;
+
+        Ok(self.inner)
+    }
+
+    /// Pass on the output up to the first held back collection.
+    fn pass_on(&mut self) -> Result<(), std::fmt::Error> {
+        let held_from = match self.frames.iter().find(|frame| frame.is_held()) {
+            Some(frame) => frame.start - self.passed_on,
+            None => self.output.len(),
+        };
+        let held_back = self.output.split_off(held_from);
+        let passed_on = std::mem::replace(&mut self.output, held_back);
+        self.passed_on += passed_on.len();
+
+        self.inner.write_str(&passed_on)
+    }
+
+    /// Write the header for `length` items before the opening delimiter at offset `start`.
+    fn insert_header(&mut self, start: usize, length: ReportedLength) {
+        let mut header = String::new();
+        // Writing to a `String` cannot fail.
+        let _ = self.options.format_header(&mut header, length);
+        self.output.insert_str(start - self.passed_on, &header);
+    }
+
+    fn write_structure_char(&mut self, c: char) {
+        if self.literal.is_some() && self.continue_literal(c) {
+            return;
+        }
+
+        if self.suppressed_nesting > 0 {
+            match c {
+                '[' | '{' | '(' => self.suppressed_nesting += 1,
+                ']' | '}' | ')' => self.suppressed_nesting -= 1,
+                '"' | '\'' if self.starts_literal(c) => self.start_literal(c),
+                _ => {}
+            }
+            return;
+        }
+
+        match c {
+            ',' => self.end_item(),
+            ']' | '}' | ')' => self.close_frame(c),
+            _ if c.is_whitespace() => self.write_whitespace(c),
+            _ => {
+                self.start_item();
+                if matches!(c, '[' | '{' | '(') {
+                    self.open_frame(c);
+                } else {
+                    if self.starts_literal(c) {
+                        self.start_literal(c);
+                    }
+                    self.write_item_char(c);
+                }
+            }
+        }
+    }
+
+    /// Pass `c` on as part of the open literal, unless it shows what looked like a char literal
+    /// is a lifetime, e.g. `'a>`, in which case the literal is ended and `false` returned.
+    fn continue_literal(&mut self, c: char) -> bool {
+        let literal = match &mut self.literal {
+            Some(literal) => literal,
+            None => return false,
+        };
+        if literal.quote == '\'' && literal.length == 1 && !literal.escaped && c != '\'' {
+            self.literal = None;
+            return false;
+        }
+
+        literal.length += 1;
+        if literal.escaped {
+            literal.escaped = false;
+        } else if c == '\\' {
+            literal.escaped = true;
+        } else if c == literal.quote {
+            self.literal = None;
+        }
+        self.write_item_char(c);
+        true
+    }
+
+    /// Whether `c` opens a literal. A `'` only does so where a char literal could start,
+    /// so e.g. an apostrophe in a custom `Debug` impl is not mistaken for one.
+    fn starts_literal(&self, c: char) -> bool {
+        match c {
+            '"' => true,
+            '\'' => match self.previous {
+                None => true,
+                Some(previous) => {
+                    previous.is_whitespace() || matches!(previous, '[' | '{' | '(' | ',' | ':')
+                }
+            },
+            _ => false,
+        }
+    }
+
+    fn start_literal(&mut self, quote: char) {
+        self.literal = Some(Literal {
+            quote,
+            escaped: false,
+            length: 0,
+        });
+    }
+
+    /// Pass `c` on as part of the current item, unless the item is not shown.
+    fn write_item_char(&mut self, c: char) {
+        match self.frames.last_mut() {
+            Some(frame) if frame.is_hidden() => frame.pending_separator.clear(),
+            Some(frame) if frame.is_suppressed() => {}
+            _ => self.output.push(c),
+        }
+    }
+
+    fn write_whitespace(&mut self, c: char) {
+        match self.frames.last_mut() {
+            Some(frame) if frame.kind == FrameKind::Collapsed => {}
+            Some(frame)
+                if frame.kind == FrameKind::Collection && (frame.is_hidden() || !frame.in_item) =>
+            {
+                frame.pending_separator.push(c)
+            }
+            _ => self.output.push(c),
+        }
+    }
+
+    fn end_item(&mut self) {
+        match self.frames.last_mut() {
+            Some(frame) => {
+                frame.in_item = false;
+                match frame.kind {
+                    FrameKind::Collection if frame.is_hidden() => frame.pending_separator.clear(),
+                    FrameKind::Collection => frame.pending_separator.push(','),
+                    FrameKind::Fields => self.output.push(','),
+                    FrameKind::Collapsed => {}
+                }
+            }
+            None => self.output.push(','),
+        }
+    }
+
+    fn start_item(&mut self) {
+        let max_length = self.options.head_length;
+        let outermost_held = self.frames.iter().position(Frame::is_held);
+        let index = match self.frames.len().checked_sub(1) {
+            Some(index) => index,
+            None => return,
+        };
+        let frame = &mut self.frames[index];
+        if frame.in_item {
+            return;
+        }
+        frame.in_item = true;
+        frame.items += 1;
+
+        match frame.kind {
+            FrameKind::Collapsed if frame.items == 1 => self.output.push_str(COLLAPSED_MARKER),
+            FrameKind::Collection if !frame.is_hidden() => {
+                let separator = std::mem::take(&mut frame.pending_separator);
+                if frame.items <= max_length {
+                    self.output.push_str(&separator);
+                    return;
+                }
+
+                let whitespace = separator.trim_start_matches(',');
+                let pretty = whitespace.contains('\n');
+                if pretty && separator.starts_with(',') {
+                    self.output.push(',');
+                }
+                frame.hidden_item_whitespace = Some(whitespace.to_string());
+                // Only the outermost held back collection is cut before it closes,
+                // any nested in it get their exact length when they close.
+                if outermost_held == Some(index) {
+                    frame.header_written = true;
+                    let (start, items) = (frame.start, frame.items);
+                    self.insert_header(start, ReportedLength::AtLeast(items));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn open_frame(&mut self, opener: char) {
+        if let Some(frame) = self.frames.last_mut().filter(|frame| frame.is_suppressed()) {
+            frame.pending_separator.clear();
+            self.suppressed_nesting += 1;
+            return;
+        }
+
+        let follows_name = match self.previous_token {
+            Some(previous) => previous.is_alphanumeric() || previous == '_',
+            None => false,
+        };
+        let kind = if self.frames.len() >= self.max_depth {
+            FrameKind::Collapsed
+        } else if opener == '(' || (opener == '{' && follows_name) {
+            FrameKind::Fields
+        } else {
+            FrameKind::Collection
+        };
+        let start = self.passed_on + self.output.len();
+        self.output.push(opener);
+        self.frames.push(Frame::new(kind, start));
+    }
+
+    fn close_frame(&mut self, closer: char) {
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => {
+                self.output.push(closer);
+                return;
+            }
+        };
+
+        match &frame.hidden_item_whitespace {
+            Some(hidden_item_whitespace) => {
+                if !frame.header_written {
+                    self.insert_header(frame.start, ReportedLength::Exact(frame.items));
+                }
+                if hidden_item_whitespace.contains('\n') {
+                    self.output.push_str(hidden_item_whitespace);
+                    self.output.push_str(&self.options.marker);
+                    self.output.push(',');
+                    self.output.push_str(&frame.pending_separator);
+                    self.output.push(closer);
+                } else {
+                    self.output.push_str(&frame.pending_separator);
+                    self.output.push(closer);
+                    self.output.push_str(&self.options.marker);
+                }
+            }
+            None => {
+                self.output.push_str(&frame.pending_separator);
+                self.output.push(closer);
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for StructureTruncatingWriter<W> {
+    fn write_str(&mut self, s: &str) -> Result<(), std::fmt::Error> {
+        for c in s.chars() {
+            self.write_structure_char(c);
+            self.previous = Some(c);
+            if !c.is_whitespace() {
+                self.previous_token = Some(c);
+            }
+        }
+
+        self.pass_on()
+    }
+}
+
+/// Limits the nesting depth and sequence length of the `Debug` output of any value,
+/// as `StructureTruncatingWriter` does.
+/// The value is formatted with `{:?}`, or `{:#?}` for alternate, so other flags do not reach it.
+/// ```rust
+/// use strings::debug_structure_truncation::DebugMaxStructure;
+/// let data = vec![vec![1, 2], vec![3], vec![4, 5, 6]];
+/// assert_eq!(
+///     format!("{:?}", DebugMaxStructure::new(&data, 1, 2)),
+///     "(length≥3 truncated to 2) [[…], […]]..."
+/// );
+/// assert_eq!(
+///     format!("{:?}", DebugMaxStructure::new(&data, 2, 2)),
+///     "(length≥3 truncated to 2) [[1, 2], [3]]..."
+/// );
+/// ```
+pub struct DebugMaxStructure<T> {
+    value: T,
+    max_depth: usize,
+    options: TruncationOptions,
+}
+
+impl<T> DebugMaxStructure<T> {
+    pub fn new(value: T, max_depth: usize, max_length: usize) -> Self {
+        Self::with_options(
+            value,
+            max_depth,
+            TruncationOptions::new().max_length(max_length),
+        )
+    }
+
+    /// Truncate sequences as `StructureTruncatingWriter::with_options` does.
+    pub fn with_options(value: T, max_depth: usize, options: TruncationOptions) -> Self {
+        Self {
+            value,
+            max_depth,
+            options,
+        }
+    }
+}
+
+impl<T: Debug> Debug for DebugMaxStructure<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let alternate = f.alternate();
+        let mut writer =
+            StructureTruncatingWriter::with_options(&mut *f, self.max_depth, self.options.clone());
+        if alternate {
+            write!(writer, "{:#?}", self.value)?;
+        } else {
+            write!(writer, "{:?}", self.value)?;
+        }
+        writer.finish().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_structure_truncating_writer {
+        use super::super::StructureTruncatingWriter;
+        use std::fmt::Write;
+
+        fn truncate_string_for(inputs: &[&str], max_depth: usize, max_length: usize) -> String {
+            let mut writer = StructureTruncatingWriter::new(String::new(), max_depth, max_length);
+            for input in inputs {
+                writer.write_str(input).unwrap();
+            }
+
+            writer.finish().unwrap()
+        }
+
+        #[test]
+        fn structure_truncating_writer_across_writes() {
+            let result = truncate_string_for(&["Some", "([1", ", 2, ", "3])"], 3, 2);
+
+            assert_eq!(result, "Some((length≥3 truncated to 2) [1, 2]...)");
+        }
+
+        #[test]
+        fn structure_truncating_writer_fields_not_truncated() {
+            let result = truncate_string_for(&["x: Foo { a: (1, 2), b: [3, 4] }"], 3, 1);
+
+            assert_eq!(
+                result,
+                "x: Foo { a: (1, 2), b: (length≥2 truncated to 1) [3]... }"
+            );
+        }
+
+        #[test]
+        fn structure_truncating_writer_map_after_field_name_truncated() {
+            let result = truncate_string_for(&["Foo { map: {1: 2, 3: 4} }"], 3, 1);
+
+            assert_eq!(result, "Foo { map: (length≥2 truncated to 1) {1: 2}... }");
+        }
+
+        #[test]
+        fn structure_truncating_writer_nested_in_held_back_gets_exact_length() {
+            let result = truncate_string_for(&["[[1, 2, 3], [4]]"], 2, 2);
+
+            assert_eq!(result, "[(length=3 truncated to 2) [1, 2]..., [4]]");
+        }
+
+        #[test]
+        fn structure_truncating_writer_streams_once_cut() {
+            let mut output = String::new();
+            let mut writer = StructureTruncatingWriter::new(&mut output, 3, 2);
+            writer.write_str("Foo { a: 1, b: [1, 2, 3, 4").unwrap();
+            drop(writer);
+
+            assert_eq!(output, "Foo { a: 1, b: (length≥3 truncated to 2) [1, 2");
+        }
+
+        #[test]
+        fn structure_truncating_writer_ignores_delimiters_in_literals() {
+            let result = truncate_string_for(&["[\"a, [b\\\"\", ']', 'c']"], 1, 2);
+
+            assert_eq!(result, "(length≥3 truncated to 2) [\"a, [b\\\"\", ']']...");
+        }
+
+        #[test]
+        fn structure_truncating_writer_escaped_quote_char() {
+            let result = truncate_string_for(&["['\\'', '[', '\\u{5b}', 'x']"], 1, 3);
+
+            assert_eq!(
+                result,
+                "(length≥4 truncated to 3) ['\\'', '[', '\\u{5b}']..."
+            );
+        }
+
+        #[test]
+        fn structure_truncating_writer_lifetime_is_not_a_literal() {
+            let result = truncate_string_for(&["[Ref<'a, 'static>, [1]]"], 1, 5);
+
+            assert_eq!(result, "[Ref<'a, 'static>, […]]");
+        }
+
+        #[test]
+        fn structure_truncating_writer_apostrophe_is_not_a_literal() {
+            let result = truncate_string_for(&["[Mark's, [1], 2]"], 1, 5);
+
+            assert_eq!(result, "[Mark's, […], 2]");
+        }
+
+        #[test]
+        fn structure_truncating_writer_empty_sequences() {
+            let result = truncate_string_for(&["[[], {}]"], 1, 0);
+
+            assert_eq!(result, "(length≥1 truncated to 0) []...");
+        }
+
+        #[test]
+        fn structure_truncating_writer_unbalanced() {
+            let result = truncate_string_for(&["a] [b, c"], 2, 5);
+
+            assert_eq!(result, "a] [b, c");
+        }
+    }
+
+    mod test_debug_max_structure {
+        use super::super::DebugMaxStructure;
+        use crate::debug_truncation_options::TruncationOptions;
+        use std::collections::BTreeMap;
+        use std::fmt::Debug;
+
+        #[derive(Debug)]
+        struct StructWithAMap {
+            #[allow(dead_code)]
+            name: &'static str,
+            #[allow(dead_code)]
+            map: BTreeMap<i32, Vec<i32>>,
+        }
+
+        fn struct_with_a_map() -> StructWithAMap {
+            StructWithAMap {
+                name: "[map]",
+                map: (1..=3).map(|i| (i, (0..i).collect())).collect(),
+            }
+        }
+
+        fn truncate_debug_string_for<T: Debug>(
+            input: T,
+            max_depth: usize,
+            max_length: usize,
+        ) -> String {
+            let result = format!("{:?}", DebugMaxStructure::new(input, max_depth, max_length));
+
+            result
+        }
+
+        #[test]
+        fn debug_max_structure_matches_untruncated() {
+            let expected = format!("{:?}", struct_with_a_map());
+            let result = truncate_debug_string_for(struct_with_a_map(), 3, 3);
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn debug_max_structure_pretty_matches_untruncated() {
+            let expected = format!("{:#?}", struct_with_a_map());
+            let result = format!("{:#?}", DebugMaxStructure::new(struct_with_a_map(), 3, 3));
+
+            assert_eq!(result, expected);
+        }
+
+        #[test]
+        fn debug_max_structure_collapses_deeper_than_max_depth() {
+            let result = truncate_debug_string_for(struct_with_a_map(), 2, 3);
+
+            assert_eq!(
+                result,
+                "StructWithAMap { name: \"[map]\", map: {1: […], 2: […], 3: […]} }"
+            );
+        }
+
+        #[test]
+        fn debug_max_structure_truncates_longer_than_max_length() {
+            let result = truncate_debug_string_for(struct_with_a_map(), 3, 2);
+
+            assert_eq!(
+                result,
+                "StructWithAMap { name: \"[map]\", \
+                 map: (length≥3 truncated to 2) {1: [0], 2: [0, 1]}... }"
+            );
+        }
+
+        #[test]
+        fn debug_max_structure_zero_depth() {
+            let result = truncate_debug_string_for(struct_with_a_map(), 0, 3);
+
+            assert_eq!(result, "StructWithAMap {…}");
+        }
+
+        #[test]
+        fn debug_max_structure_pretty() {
+            let result = format!(
+                "{:#?}",
+                DebugMaxStructure::new(vec![vec![1, 2], vec![3]], 2, 1)
+            );
+
+            assert_eq!(
+                result,
+                "(length≥2 truncated to 1) [\n    (length=2 truncated to 1) [\n        1,\n        ...,\n    ],\n    ...,\n]"
+            );
+        }
+
+        #[test]
+        fn debug_max_structure_with_options() {
+            let options = TruncationOptions::new()
+                .max_length(1)
+                .marker("…")
+                .show_header(false);
+            let result = format!("{:?}", DebugMaxStructure::with_options([1, 2], 1, options));
+
+            assert_eq!(result, "[1]…");
+        }
+    }
+}
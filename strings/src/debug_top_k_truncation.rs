@@ -0,0 +1,341 @@
+use crate::debug_sorted_truncation::{smallest_sorted, OrderedBy};
+use crate::debug_truncation::{debug_chars_of, CappedElement, ContainerShape};
+use crate::debug_truncation_options::{ReportedLength, TruncationOptions, ZeroLengthDisplay};
+use std::cmp::Reverse;
+use std::fmt::{Debug, Formatter};
+
+/// Which end of the key order a `TopKDebug` shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KeyOrder {
+    Largest,
+    Smallest,
+}
+
+impl KeyOrder {
+    fn label(self) -> &'static str {
+        match self {
+            KeyOrder::Largest => "top",
+            KeyOrder::Smallest => "bottom",
+        }
+    }
+}
+
+/// Shows the `k` items with the largest, or smallest, key of all items of `source`,
+/// or as many as the format precision if set, e.g. `(length=100000 top 10 by key) [..]...`.
+/// Items with equal keys are shown in the order `source` yields them.
+/// Each time it is formatted `source` is iterated once, keeping at most `k` items,
+/// so it may be a plain iterator as well as an exact-size one.
+///
+/// Of its `TruncationOptions`, `k` is the head length, and the marker, header setting,
+/// `max_element_chars` and `zero_length` apply as elsewhere. `max_total_chars` keeps as many
+/// whole items of the `k` as fit, without splitting the last one.
+/// A tail and sampling do not apply, as only the `k` items in key order are kept.
+pub struct TopKDebug<S, F> {
+    source: S,
+    key_fn: F,
+    order: KeyOrder,
+    options: TruncationOptions,
+}
+
+impl<S, D, K, F> Debug for TopKDebug<S, F>
+where
+    S: IntoIterator<Item = D> + Clone,
+    D: Debug,
+    K: Ord,
+    F: Fn(&D) -> K,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        let k = options.head_length;
+        let key_fn = &self.key_fn;
+        let items = self.source.clone().into_iter().enumerate();
+        let (selected, length) = match self.order {
+            KeyOrder::Largest => {
+                let (selected, length) = smallest_sorted(
+                    items
+                        .map(|(index, item)| OrderedBy::new((Reverse(key_fn(&item)), index), item)),
+                    k,
+                );
+                (
+                    selected
+                        .into_iter()
+                        .map(|entry| entry.item)
+                        .collect::<Vec<_>>(),
+                    length,
+                )
+            }
+            KeyOrder::Smallest => {
+                let (selected, length) = smallest_sorted(
+                    items.map(|(index, item)| OrderedBy::new((key_fn(&item), index), item)),
+                    k,
+                );
+                (
+                    selected.into_iter().map(|entry| entry.item).collect(),
+                    length,
+                )
+            }
+        };
+
+        let mut entries = selected
+            .into_iter()
+            .map(|item| CappedElement::new(item, &options))
+            .collect::<Vec<_>>();
+        if let Some(max_total_chars) = options.max_total_chars {
+            let mut total_chars = 0;
+            let mut fitting_length = 0;
+            for entry in &entries {
+                total_chars += debug_chars_of(entry, f)?;
+                if total_chars > max_total_chars {
+                    break;
+                }
+                fitting_length += 1;
+            }
+            entries.truncate(fitting_length);
+        }
+
+        let shown_length = entries.len();
+        if shown_length == 0 && length > 0 {
+            match options.zero_length {
+                ZeroLengthDisplay::Length => return write!(f, "(length={})", length),
+                ZeroLengthDisplay::Marker => return f.write_str(&options.marker),
+                ZeroLengthDisplay::EmptyContainer => {}
+            }
+        }
+        options.format_ordered_header(
+            f,
+            ReportedLength::Exact(length),
+            self.order.label(),
+            shown_length,
+        )?;
+        if length > shown_length {
+            ContainerShape::List.format_debug_truncated_entries(f, entries, &options.marker)
+        } else {
+            ContainerShape::List.format_debug_entries(f, entries)
+        }
+    }
+}
+
+/// Shows the `k` items of `source` with the largest `key_fn(&item)`, largest first.
+/// ```rust
+/// use strings::debug_top_k_truncation::top_k_debug;
+/// let latencies = (0..100_000).map(|i| (i * 7919) % 100_000).collect::<Vec<_>>();
+/// assert_eq!(
+///     format!("{:?}", top_k_debug(&latencies, 3, |latency| **latency)),
+///     "(length=100000 top 3 by key) [99999, 99998, 99997]..."
+/// );
+/// ```
+pub fn top_k_debug<S, D, K, F>(source: S, k: usize, key_fn: F) -> TopKDebug<S, F>
+where
+    S: IntoIterator<Item = D> + Clone,
+    K: Ord,
+    F: Fn(&D) -> K,
+{
+    top_k_debug_with_options(source, key_fn, TruncationOptions::new().max_length(k))
+}
+
+/// Shows the `options.head_length` items of `source` with the largest `key_fn(&item)`, largest first,
+/// with the options `TopKDebug` applies.
+pub fn top_k_debug_with_options<S, D, K, F>(
+    source: S,
+    key_fn: F,
+    options: TruncationOptions,
+) -> TopKDebug<S, F>
+where
+    S: IntoIterator<Item = D> + Clone,
+    K: Ord,
+    F: Fn(&D) -> K,
+{
+    let head_length = options.head_length;
+    TopKDebug {
+        source,
+        key_fn,
+        order: KeyOrder::Largest,
+        options: options.max_length(head_length),
+    }
+}
+
+/// Shows the `k` items of `source` with the smallest `key_fn(&item)`, smallest first.
+pub fn bottom_k_debug<S, D, K, F>(source: S, k: usize, key_fn: F) -> TopKDebug<S, F>
+where
+    S: IntoIterator<Item = D> + Clone,
+    K: Ord,
+    F: Fn(&D) -> K,
+{
+    bottom_k_debug_with_options(source, key_fn, TruncationOptions::new().max_length(k))
+}
+
+/// Shows the `options.head_length` items of `source` with the smallest `key_fn(&item)`, smallest first,
+/// with the options `TopKDebug` applies.
+pub fn bottom_k_debug_with_options<S, D, K, F>(
+    source: S,
+    key_fn: F,
+    options: TruncationOptions,
+) -> TopKDebug<S, F>
+where
+    S: IntoIterator<Item = D> + Clone,
+    K: Ord,
+    F: Fn(&D) -> K,
+{
+    let head_length = options.head_length;
+    TopKDebug {
+        source,
+        key_fn,
+        order: KeyOrder::Smallest,
+        options: options.max_length(head_length),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_top_k_debug {
+        use super::super::{
+            bottom_k_debug, bottom_k_debug_with_options, top_k_debug, top_k_debug_with_options,
+        };
+        use crate::debug_truncation_options::{TruncationOptions, ZeroLengthDisplay};
+        use std::collections::HashMap;
+        use std::fmt::{Debug, Formatter};
+
+        /// Example struct which shows its slowest requests.
+        struct StructWithLatencies {
+            latencies: HashMap<&'static str, u32>,
+            k: usize,
+        }
+
+        impl Debug for StructWithLatencies {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                f.debug_struct("StructWithLatencies")
+                    .field(
+                        "slowest",
+                        &top_k_debug(&self.latencies, self.k, |(_, latency)| **latency),
+                    )
+                    .field(
+                        "fastest",
+                        &bottom_k_debug(&self.latencies, self.k, |(_, latency)| **latency),
+                    )
+                    .finish()
+            }
+        }
+
+        fn truncate_debug_string_for(k: usize) -> String {
+            let input = StructWithLatencies {
+                latencies: vec![("a", 30), ("b", 10), ("c", 50), ("d", 20)]
+                    .into_iter()
+                    .collect(),
+                k,
+            };
+            format!("{:?}", input)
+        }
+
+        #[test]
+        fn top_k_debug_longer_than_k() {
+            let result = truncate_debug_string_for(2);
+
+            assert_eq!(
+                result,
+                "StructWithLatencies { \
+                 slowest: (length=4 top 2 by key) [(\"c\", 50), (\"a\", 30)]..., \
+                 fastest: (length=4 bottom 2 by key) [(\"b\", 10), (\"d\", 20)]... }"
+            );
+        }
+
+        #[test]
+        fn top_k_debug_shorter_than_k() {
+            let result = format!("{:?}", top_k_debug(&[2, 3, 1], 5, |item| **item));
+
+            assert_eq!(result, "(length=3 top 3 by key) [3, 2, 1]");
+        }
+
+        #[test]
+        fn top_k_debug_zero_k() {
+            let result = format!("{:?}", bottom_k_debug(&[2, 3, 1], 0, |item| **item));
+
+            assert_eq!(result, "(length=3)");
+        }
+
+        #[test]
+        fn top_k_debug_zero_k_empty_container() {
+            let options = TruncationOptions::new()
+                .max_length(0)
+                .zero_length(ZeroLengthDisplay::EmptyContainer);
+            let result = format!(
+                "{:?}",
+                bottom_k_debug_with_options(&[2, 3, 1], |item| **item, options)
+            );
+
+            assert_eq!(result, "(length=3 bottom 0 by key) []...");
+        }
+
+        #[test]
+        fn top_k_debug_empty_source() {
+            let result = format!("{:?}", top_k_debug(Vec::<i32>::new(), 2, |item| *item));
+
+            assert_eq!(result, "(length=0 top 0 by key) []");
+        }
+
+        #[test]
+        fn top_k_debug_with_options_max_total_chars() {
+            let options = TruncationOptions::new().max_length(3).max_total_chars(5);
+            let result = format!("{:?}", top_k_debug_with_options(1..=200, |i| *i, options));
+
+            assert_eq!(result, "(length=200 top 1 by key) [200]...");
+        }
+
+        #[test]
+        fn top_k_debug_with_options_ignores_tail() {
+            let options = TruncationOptions::new().head_and_tail(2, 1);
+            let result = format!("{:?}", top_k_debug_with_options(1..=10, |i| *i, options));
+            let precise = format!(
+                "{:.3?}",
+                top_k_debug_with_options(
+                    1..=10,
+                    |i| *i,
+                    TruncationOptions::new().head_and_tail(2, 1)
+                )
+            );
+
+            assert_eq!(result, "(length=10 top 2 by key) [10, 9]...");
+            assert_eq!(precise, "(length=10 top 3 by key) [10, 9, 8]...");
+        }
+
+        #[test]
+        fn top_k_debug_plain_iterator() {
+            let evens = (1..=100).filter(|i| i % 2 == 0);
+            let result = format!("{:?}", top_k_debug(evens, 2, |i| *i % 10));
+
+            assert_eq!(result, "(length=50 top 2 by key) [8, 18]...");
+        }
+
+        #[test]
+        fn bottom_k_debug_equal_keys_in_source_order() {
+            let result = format!("{:?}", bottom_k_debug(1..=10, 3, |i| *i % 3));
+
+            assert_eq!(result, "(length=10 bottom 3 by key) [3, 6, 9]...");
+        }
+
+        #[test]
+        fn top_k_debug_with_options_without_header() {
+            let options = TruncationOptions::new()
+                .max_length(2)
+                .show_header(false)
+                .marker("~")
+                .max_element_chars(2);
+            let result = format!("{:?}", top_k_debug_with_options(90..=110, |i| *i, options));
+
+            assert_eq!(result, "[11…(+1 chars), 10…(+1 chars)]~");
+        }
+
+        #[test]
+        fn top_k_debug_precision_overrides_k() {
+            let result = format!("{:.1?}", top_k_debug(1..=10, 3, |i| *i));
+
+            assert_eq!(result, "(length=10 top 1 by key) [10]...");
+        }
+
+        #[test]
+        fn top_k_debug_pretty() {
+            let result = format!("{:#?}", bottom_k_debug(1..=10, 1, |i| *i));
+
+            assert_eq!(result, "(length=10 bottom 1 by key) [\n    1,\n    ...,\n]");
+        }
+    }
+}
@@ -0,0 +1,1204 @@
+use crate::debug_sampled_truncation::format_debug_shaped_sampled;
+use crate::debug_truncation_options::{ReportedLength, TruncationOptions, ZeroLengthDisplay};
+use std::cell::Cell;
+use std::fmt::{Debug, Formatter, Write};
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter`,
+/// truncating to the specified length.
+pub fn format_debug_truncated_to_max_length<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    max_length: usize,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    format_debug_shaped_truncated_to_max_length(
+        f,
+        exact_into_iter,
+        max_length,
+        ContainerShape::List,
+    )
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter`,
+/// truncating as specified by `options`.
+/// When `options.sampling` is set and the iterator is longer than the item limit,
+/// the sampled items are written whole, without the `max_total_chars` budget.
+pub fn format_debug_truncated_with_options<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    options: &TruncationOptions,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    format_debug_shaped_truncated_with_options(f, exact_into_iter, options, ContainerShape::List)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter`,
+/// truncating to the specified length and capping each item to `max_element_chars` chars,
+/// e.g. `(length=5 truncated to 2) ["abc…"(+9812 chars), "d"]...`.
+pub fn format_debug_truncated_to_max_length_and_element_chars<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    max_length: usize,
+    max_element_chars: usize,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let options = TruncationOptions::new()
+        .max_length(max_length)
+        .max_element_chars(max_element_chars);
+    format_debug_truncated_with_options(f, exact_into_iter, &options)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter`,
+/// showing as many whole items as fit within `max_total_chars` chars of item output.
+pub fn format_debug_truncated_to_max_chars<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    max_total_chars: usize,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let options = TruncationOptions::new()
+        .max_length(usize::MAX)
+        .max_total_chars(max_total_chars);
+    format_debug_truncated_with_options(f, exact_into_iter, &options)
+}
+
+/// Delimiters a container uses for its untruncated `Debug` output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContainerShape {
+    /// `[a, b]`, as written by `Formatter::debug_list`.
+    List,
+    /// `{a, b}`, as written by `Formatter::debug_set`.
+    Set,
+}
+
+impl ContainerShape {
+    pub(crate) fn format_debug_entries<D: Debug>(
+        self,
+        f: &mut Formatter<'_>,
+        entries: impl IntoIterator<Item = D>,
+    ) -> Result<(), std::fmt::Error> {
+        match self {
+            ContainerShape::List => f.debug_list().entries(entries).finish(),
+            ContainerShape::Set => f.debug_set().entries(entries).finish(),
+        }
+    }
+
+    /// Write `entries` followed by `marker`.
+    /// For `{:#?}` the marker is written as a final entry so it sits on its own indented line,
+    /// otherwise it follows the closing delimiter.
+    pub(crate) fn format_debug_truncated_entries<D: Debug>(
+        self,
+        f: &mut Formatter<'_>,
+        entries: impl IntoIterator<Item = D>,
+        marker: &str,
+    ) -> Result<(), std::fmt::Error> {
+        if f.alternate() {
+            let marker = TruncationMarker(marker);
+            match self {
+                ContainerShape::List => f.debug_list().entries(entries).entry(&marker).finish(),
+                ContainerShape::Set => f.debug_set().entries(entries).entry(&marker).finish(),
+            }
+        } else {
+            self.format_debug_entries(f, entries)?;
+            f.write_str(marker)
+        }
+    }
+
+    /// Write the first `head_length` of `entries`, then `marker` as an entry,
+    /// then what is left of `entries` after skipping `skip_length` more.
+    pub(crate) fn format_debug_head_and_tail_entries<D: Debug>(
+        self,
+        f: &mut Formatter<'_>,
+        mut entries: impl Iterator<Item = D>,
+        head_length: usize,
+        skip_length: usize,
+        marker: &str,
+    ) -> Result<(), std::fmt::Error> {
+        let marker = TruncationMarker(marker);
+        match self {
+            ContainerShape::List => {
+                let mut debug_list = f.debug_list();
+                debug_list
+                    .entries(entries.by_ref().take(head_length))
+                    .entry(&marker);
+                if skip_length > 0 {
+                    entries.nth(skip_length - 1);
+                }
+                debug_list.entries(entries).finish()
+            }
+            ContainerShape::Set => {
+                let mut debug_set = f.debug_set();
+                debug_set
+                    .entries(entries.by_ref().take(head_length))
+                    .entry(&marker);
+                if skip_length > 0 {
+                    entries.nth(skip_length - 1);
+                }
+                debug_set.entries(entries).finish()
+            }
+        }
+    }
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter` using the
+/// delimiters of `shape`, truncating to the specified length.
+pub fn format_debug_shaped_truncated_to_max_length<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    max_length: usize,
+    shape: ContainerShape,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let options = TruncationOptions::new().max_length(max_length);
+    format_debug_shaped_truncated_with_options(f, exact_into_iter, &options, shape)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter` using the
+/// delimiters of `shape`, truncating as specified by `options`.
+/// Items between the head and the tail are skipped with `Iterator::nth`, so are never formatted.
+/// When `options.sampling` is set and the iterator is longer than the item limit,
+/// the sampled items are written whole, without the `max_total_chars` budget.
+pub fn format_debug_shaped_truncated_with_options<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    options: &TruncationOptions,
+    shape: ContainerShape,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let exact_iter = exact_into_iter.into_iter();
+    let reported_length = ReportedLength::Exact(exact_iter.len());
+    format_debug_shaped_truncated_reporting(f, exact_iter, options, shape, reported_length)
+}
+
+/// As `format_debug_shaped_truncated_with_options`, with the headers reporting `reported_length`
+/// rather than the number of items, e.g. when each item stands for a run of elements.
+pub(crate) fn format_debug_shaped_truncated_reporting<D, ESI>(
+    f: &mut Formatter<'_>,
+    exact_iter: ESI,
+    options: &TruncationOptions,
+    shape: ContainerShape,
+    reported_length: ReportedLength,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+{
+    let exact_iter = exact_iter.map(|element| CappedElement::new(element, options));
+    let debug_exact_iter_length = exact_iter.len();
+    let shown_length = options.shown_length();
+    let sampling = options
+        .sampling
+        .filter(|_| shown_length > 0 && debug_exact_iter_length > shown_length);
+    let max_total_chars = options.max_total_chars.filter(|_| shown_length > 0);
+
+    if let Some(sampling) = sampling {
+        format_debug_shaped_sampled(f, exact_iter, options, sampling, shape, reported_length)
+    } else if let Some(max_total_chars) = max_total_chars {
+        format_debug_shaped_within_char_budget(
+            f,
+            exact_iter,
+            options,
+            max_total_chars,
+            shape,
+            reported_length,
+        )
+    } else if debug_exact_iter_length <= shown_length {
+        shape.format_debug_entries(f, exact_iter)
+    } else if shown_length == 0 {
+        match options.zero_length {
+            ZeroLengthDisplay::Length => write!(f, "(length{})", reported_length),
+            ZeroLengthDisplay::Marker => f.write_str(&options.marker),
+            ZeroLengthDisplay::EmptyContainer => {
+                options.format_header(f, reported_length)?;
+                shape.format_debug_truncated_entries(f, std::iter::empty::<()>(), &options.marker)
+            }
+        }
+    } else {
+        options.format_header(f, reported_length)?;
+        if !options.shows_tail() {
+            shape.format_debug_truncated_entries(
+                f,
+                exact_iter.take(options.head_length),
+                &options.marker,
+            )
+        } else {
+            shape.format_debug_head_and_tail_entries(
+                f,
+                exact_iter,
+                options.head_length,
+                debug_exact_iter_length - shown_length,
+                &options.marker,
+            )
+        }
+    }
+}
+
+/// Write as many whole items of `exact_iter` as fit within `max_total_chars` chars of item output,
+/// taking up to `options.head_length` from the front, then up to `options.tail_length` from the back.
+fn format_debug_shaped_within_char_budget<D, ESI>(
+    f: &mut Formatter<'_>,
+    mut exact_iter: ESI,
+    options: &TruncationOptions,
+    max_total_chars: usize,
+    shape: ContainerShape,
+    reported_length: ReportedLength,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+{
+    let debug_exact_iter_length = exact_iter.len();
+    let truncated = debug_exact_iter_length > options.shown_length();
+    let head_length = if truncated {
+        options.head_length
+    } else {
+        debug_exact_iter_length
+    };
+
+    let mut items = WithinCharBudget::take_head(
+        f,
+        &mut exact_iter,
+        head_length,
+        max_total_chars,
+        options.split_last_element,
+    )?;
+    if truncated && options.shows_tail() {
+        let skip_length = debug_exact_iter_length - options.tail_length - items.taken_length;
+        if skip_length > 0 {
+            exact_iter.nth(skip_length - 1);
+        }
+        items.take_tail(f, exact_iter.collect())?;
+    }
+    items.format(
+        f,
+        options,
+        Some(reported_length),
+        truncated,
+        shape,
+        &options.marker,
+    )
+}
+
+/// Items picked to fit within `max_total_chars` chars of item output, measured before anything is
+/// written since the header reports how many fit.
+/// Items are measured as formatted with the formatter's flags, so e.g. `{:x?}` output is measured
+/// by its hex length, apart from fill and alignment, which apply to the whole output rather than items.
+pub(crate) struct WithinCharBudget<D> {
+    head: Vec<BudgetedEntry<D>>,
+    /// Items from the back, if a tail is shown.
+    tail: Option<Vec<BudgetedEntry<D>>>,
+    /// Items taken from the front, including the first which did not fit.
+    pub(crate) taken_length: usize,
+    pub(crate) budget_reached: bool,
+    remaining_chars: usize,
+    max_total_chars: usize,
+}
+
+impl<D: Debug> WithinCharBudget<D> {
+    /// Take up to `max_length` items from the front of `iter` while they fit,
+    /// then, with `split_last_element`, the start of the first which does not,
+    /// unless that start is only its opening delimiters.
+    pub(crate) fn take_head<I: Iterator<Item = D>>(
+        f: &Formatter<'_>,
+        iter: &mut I,
+        max_length: usize,
+        max_total_chars: usize,
+        split_last_element: bool,
+    ) -> Result<Self, std::fmt::Error> {
+        let mut items = Self {
+            head: Vec::new(),
+            tail: None,
+            taken_length: 0,
+            budget_reached: false,
+            remaining_chars: max_total_chars,
+            max_total_chars,
+        };
+        for element in iter.take(max_length) {
+            items.taken_length += 1;
+            let element_chars = debug_chars_of(&element, f)?;
+            if element_chars <= items.remaining_chars {
+                items.remaining_chars -= element_chars;
+                items.head.push(BudgetedEntry::Whole(element));
+            } else {
+                if split_last_element && items.remaining_chars > 0 {
+                    let mut split_element = String::new();
+                    let mut writer = CharCapWriter::new(&mut split_element, items.remaining_chars);
+                    write_debug_with_flags_of(&mut writer, &element, f)?;
+                    let shows_content = writer.shows_content();
+                    writer.finish()?;
+                    if shows_content {
+                        items.remaining_chars = 0;
+                        items.head.push(BudgetedEntry::Split(split_element));
+                    }
+                }
+                items.budget_reached = true;
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Take as many of the last of `tail` as fit in what is left of the budget.
+    pub(crate) fn take_tail(
+        &mut self,
+        f: &Formatter<'_>,
+        tail: Vec<D>,
+    ) -> Result<(), std::fmt::Error> {
+        let mut shown_tail = Vec::new();
+        for element in tail.into_iter().rev() {
+            let element_chars = debug_chars_of(&element, f)?;
+            if element_chars <= self.remaining_chars {
+                self.remaining_chars -= element_chars;
+                shown_tail.push(BudgetedEntry::Whole(element));
+            } else {
+                self.budget_reached = true;
+                break;
+            }
+        }
+        shown_tail.reverse();
+        self.tail = Some(shown_tail);
+
+        Ok(())
+    }
+
+    /// Write the items picked, after the char budget header if the budget was reached,
+    /// or after the usual header if `truncated` by the item limit.
+    /// No header is written without a `reported_length`.
+    pub(crate) fn format(
+        self,
+        f: &mut Formatter<'_>,
+        options: &TruncationOptions,
+        reported_length: Option<ReportedLength>,
+        truncated: bool,
+        shape: ContainerShape,
+        marker: &str,
+    ) -> Result<(), std::fmt::Error> {
+        match (self.budget_reached, truncated, reported_length) {
+            (true, _, Some(reported_length)) => options.format_char_budget_header(
+                f,
+                reported_length,
+                self.shown_length(),
+                self.max_total_chars,
+            )?,
+            (false, true, Some(reported_length)) => options.format_header(f, reported_length)?,
+            _ => {}
+        }
+        self.format_entries(f, truncated, shape, marker)
+    }
+
+    /// Number of items picked, whole or cut to fit.
+    pub(crate) fn shown_length(&self) -> usize {
+        self.head.len() + self.tail.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Write the items picked without any header, followed by `marker` if the budget was reached
+    /// or they were already `truncated`.
+    pub(crate) fn format_entries(
+        self,
+        f: &mut Formatter<'_>,
+        truncated: bool,
+        shape: ContainerShape,
+        marker: &str,
+    ) -> Result<(), std::fmt::Error> {
+        let head_length = self.head.len();
+        let shows_tail = self.tail.is_some();
+        let entries = self.head.into_iter().chain(self.tail.unwrap_or_default());
+        if !self.budget_reached && !truncated {
+            shape.format_debug_entries(f, entries)
+        } else if shows_tail {
+            shape.format_debug_head_and_tail_entries(f, entries, head_length, 0, marker)
+        } else {
+            shape.format_debug_truncated_entries(f, entries, marker)
+        }
+    }
+}
+
+/// Item picked by `WithinCharBudget`, whole or cut to fit.
+enum BudgetedEntry<D> {
+    Whole(D),
+    Split(String),
+}
+
+impl<D: Debug> Debug for BudgetedEntry<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            BudgetedEntry::Whole(element) => element.fmt(f),
+            BudgetedEntry::Split(split_element) => f.write_str(split_element),
+        }
+    }
+}
+
+/// Number of chars `element` is written as by `write_debug_with_flags_of`.
+pub(crate) fn debug_chars_of<D: Debug>(
+    element: &D,
+    f: &Formatter<'_>,
+) -> Result<usize, std::fmt::Error> {
+    let mut counter = CharCounter(0);
+    write_debug_with_flags_of(&mut counter, element, f)?;
+    Ok(counter.0)
+}
+
+/// Write `element` to `w` with every flag of `f` but its fill and alignment.
+pub(crate) fn write_debug_with_flags_of<W: Write, D: Debug + ?Sized>(
+    w: &mut W,
+    element: &D,
+    f: &Formatter<'_>,
+) -> Result<(), std::fmt::Error> {
+    DebugFlags::of(f).write(w, element)
+}
+
+/// Flags of a `Formatter` which `DebugFlags::write` passes on to another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DebugFlags {
+    sign_plus: bool,
+    alternate: bool,
+    zero_pad: bool,
+    hex: Option<DebugHex>,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+/// The `{:x?}` or `{:X?}` flag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DebugHex {
+    Lower,
+    Upper,
+}
+
+/// Write `$element` to `$w` with the format spec flags `$flags`, e.g. `+#0`, then the width and
+/// precision if set, then `$debug_type`, e.g. `x?`.
+macro_rules! write_debug_with_spec {
+    ($w:expr, $element:expr, $width:expr, $precision:expr, $flags:literal, $debug_type:literal) => {
+        match ($width, $precision) {
+            (None, None) => write!($w, concat!("{:", $flags, $debug_type, "}"), $element),
+            (Some(width), None) => write!(
+                $w,
+                concat!("{:", $flags, "width$", $debug_type, "}"),
+                $element,
+                width = width
+            ),
+            (None, Some(precision)) => write!(
+                $w,
+                concat!("{:", $flags, ".precision$", $debug_type, "}"),
+                $element,
+                precision = precision
+            ),
+            (Some(width), Some(precision)) => write!(
+                $w,
+                concat!("{:", $flags, "width$.precision$", $debug_type, "}"),
+                $element,
+                width = width,
+                precision = precision
+            ),
+        }
+    };
+}
+
+/// As `write_debug_with_spec`, with the debug type given by `$hex`.
+macro_rules! write_debug_with_hex {
+    ($w:expr, $element:expr, $flags_of:expr, $flags:literal) => {
+        match $flags_of.hex {
+            None => write_debug_with_spec!(
+                $w,
+                $element,
+                $flags_of.width,
+                $flags_of.precision,
+                $flags,
+                "?"
+            ),
+            Some(DebugHex::Lower) => write_debug_with_spec!(
+                $w,
+                $element,
+                $flags_of.width,
+                $flags_of.precision,
+                $flags,
+                "x?"
+            ),
+            Some(DebugHex::Upper) => write_debug_with_spec!(
+                $w,
+                $element,
+                $flags_of.width,
+                $flags_of.precision,
+                $flags,
+                "X?"
+            ),
+        }
+    };
+}
+
+impl DebugFlags {
+    /// The flags of `f`. The hex flags can only be read through the deprecated `Formatter::flags`,
+    /// whose bits have stayed the same since Rust 1.0.
+    #[allow(deprecated)]
+    pub(crate) fn of(f: &Formatter<'_>) -> Self {
+        let flags = f.flags();
+        let hex = if flags & (1 << 4) != 0 {
+            Some(DebugHex::Lower)
+        } else if flags & (1 << 5) != 0 {
+            Some(DebugHex::Upper)
+        } else {
+            None
+        };
+        Self {
+            sign_plus: f.sign_plus(),
+            alternate: f.alternate(),
+            zero_pad: f.sign_aware_zero_pad(),
+            hex,
+            width: f.width(),
+            precision: f.precision(),
+        }
+    }
+
+    /// These flags with no precision.
+    pub(crate) fn without_precision(self) -> Self {
+        Self {
+            precision: None,
+            ..self
+        }
+    }
+
+    /// Write `element` to `w` with these flags.
+    pub(crate) fn write<W: Write, D: Debug + ?Sized>(
+        self,
+        w: &mut W,
+        element: &D,
+    ) -> Result<(), std::fmt::Error> {
+        match (self.sign_plus, self.alternate, self.zero_pad) {
+            (false, false, false) => write_debug_with_hex!(w, element, self, ""),
+            (false, false, true) => write_debug_with_hex!(w, element, self, "0"),
+            (false, true, false) => write_debug_with_hex!(w, element, self, "#"),
+            (false, true, true) => write_debug_with_hex!(w, element, self, "#0"),
+            (true, false, false) => write_debug_with_hex!(w, element, self, "+"),
+            (true, false, true) => write_debug_with_hex!(w, element, self, "+0"),
+            (true, true, false) => write_debug_with_hex!(w, element, self, "+#"),
+            (true, true, true) => write_debug_with_hex!(w, element, self, "+#0"),
+        }
+    }
+}
+
+/// `fmt::Write` which only counts the chars written.
+struct CharCounter(usize);
+
+impl Write for CharCounter {
+    fn write_str(&mut self, s: &str) -> Result<(), std::fmt::Error> {
+        self.0 += s.chars().count();
+        Ok(())
+    }
+}
+
+/// `Debug` entry standing in for items which are not shown, e.g. `...`.
+pub(crate) struct TruncationMarker<'marker>(pub(crate) &'marker str);
+
+impl<'marker> Debug for TruncationMarker<'marker> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.write_str(self.0)
+    }
+}
+
+/// Key/value pair written as `key: value`, so map entries can be followed by a `TruncationMarker`
+/// inside `Formatter::debug_set`, which writes the same delimiters as `Formatter::debug_map`.
+pub(crate) struct MapEntry<K, V>(pub(crate) K, pub(crate) V);
+
+impl<K: Debug, V: Debug> Debug for MapEntry<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.0.fmt(f)?;
+        f.write_str(": ")?;
+        self.1.fmt(f)
+    }
+}
+
+/// Item whose `Debug` output is capped to `options.max_element_chars` chars, when set,
+/// and written without the format precision when that is the item limit.
+pub(crate) struct CappedElement<D> {
+    element: D,
+    max_chars: Option<usize>,
+    strip_precision: bool,
+}
+
+impl<D> CappedElement<D> {
+    pub(crate) fn new(element: D, options: &TruncationOptions) -> Self {
+        Self {
+            element,
+            max_chars: options.max_element_chars,
+            strip_precision: options.strip_item_precision,
+        }
+    }
+}
+
+impl<D: Debug> Debug for CappedElement<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let flags = match (self.strip_precision, f.precision()) {
+            (true, Some(_)) => DebugFlags::of(f).without_precision(),
+            _ => DebugFlags::of(f),
+        };
+        let max_chars = match self.max_chars {
+            Some(max_chars) => max_chars,
+            None if flags.precision != f.precision() => return flags.write(f, &self.element),
+            None => return self.element.fmt(f),
+        };
+
+        let mut writer = CharCapWriter::new(&mut *f, max_chars);
+        flags.write(&mut writer, &self.element)?;
+        writer.finish()
+    }
+}
+
+/// Writes the first `remaining_chars` chars to `inner`, counting the rest as elided.
+pub(crate) struct CharCapWriter<'inner, W> {
+    inner: &'inner mut W,
+    remaining_chars: usize,
+    elided_chars: usize,
+    /// Quote of the string or char literal the written chars end inside, if any.
+    open_quote: Option<char>,
+    /// Whether the last written char is a backslash escaping the next one in a literal.
+    escaped: bool,
+    /// Whether anything but opening delimiters and whitespace was written.
+    shows_content: bool,
+    /// Whether the last char within the limit is a backslash opening an escape, held back as
+    /// the cut would leave it escaping the closing quote, e.g. `"a\…"`.
+    held_backslash: bool,
+}
+
+impl<'inner, W: Write> CharCapWriter<'inner, W> {
+    pub(crate) fn new(inner: &'inner mut W, max_chars: usize) -> Self {
+        Self {
+            inner,
+            remaining_chars: max_chars,
+            elided_chars: 0,
+            open_quote: None,
+            escaped: false,
+            shows_content: false,
+            held_backslash: false,
+        }
+    }
+
+    /// Whether the written chars show any of the output, rather than only its opening delimiters,
+    /// e.g. `"` for a string.
+    pub(crate) fn shows_content(&self) -> bool {
+        self.shows_content
+    }
+
+    /// Mark the elided chars, if any, e.g. `abc…(+9 chars)`.
+    /// A cut string literal keeps its closing quote, e.g. `"abc…"(+9 chars)` or `Some("abc…"(+9 chars)`.
+    pub(crate) fn finish(self) -> Result<(), std::fmt::Error> {
+        match (self.elided_chars, self.open_quote) {
+            (0, _) if self.held_backslash => self.inner.write_char('\\'),
+            (0, _) => Ok(()),
+            (1, Some(quote)) => self.inner.write_char(quote),
+            (elided_chars, Some(quote)) => {
+                write!(self.inner, "…{}(+{} chars)", quote, elided_chars - 1)
+            }
+            (elided_chars, None) => write!(self.inner, "…(+{} chars)", elided_chars),
+        }
+    }
+
+    /// Follow the literal quoting of a written char.
+    fn track_written_char(&mut self, written_char: char) {
+        match self.open_quote {
+            Some(_) if self.escaped => self.escaped = false,
+            Some(_) if written_char == '\\' => self.escaped = true,
+            Some(quote) if written_char == quote => self.open_quote = None,
+            Some(_) => {}
+            None if written_char == '"' || written_char == '\'' => {
+                self.open_quote = Some(written_char)
+            }
+            None => {}
+        }
+        if !matches!(written_char, '"' | '\'' | '(' | '[' | '{') && !written_char.is_whitespace() {
+            self.shows_content = true;
+        }
+    }
+}
+
+impl<'inner, W: Write> Write for CharCapWriter<'inner, W> {
+    fn write_str(&mut self, s: &str) -> Result<(), std::fmt::Error> {
+        let written_length = match s.char_indices().nth(self.remaining_chars) {
+            None => s.len(),
+            Some((index, _)) => index,
+        };
+        let elided_chars = s[written_length..].chars().count();
+        if elided_chars > 0 && self.held_backslash {
+            self.held_backslash = false;
+            self.elided_chars += 1;
+        }
+        self.elided_chars += elided_chars;
+
+        let mut written = &s[..written_length];
+        for written_char in written.chars() {
+            self.remaining_chars -= 1;
+            self.track_written_char(written_char);
+        }
+        if self.remaining_chars == 0 && self.escaped && written.ends_with('\\') {
+            written = &written[..written.len() - 1];
+            if elided_chars > 0 {
+                self.elided_chars += 1;
+            } else {
+                self.held_backslash = true;
+            }
+        }
+        self.inner.write_str(written)
+    }
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator + DoubleEndedIterator>>` of `Debug` to `Formatter`,
+/// showing only the first `head_length` and the last `tail_length` items.
+/// The items in between are skipped with `Iterator::nth`, so are never formatted.
+pub fn format_debug_truncated_to_head_and_tail<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    head_length: usize,
+    tail_length: usize,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D> + DoubleEndedIterator,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let options = TruncationOptions::new().head_and_tail(head_length, tail_length);
+    format_debug_truncated_with_options(f, exact_into_iter, &options)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator>>` of `Debug` to `Formatter`,
+/// showing only the first `head_length` and the last `tail_length` items.
+/// As the length is known up front the items in between are skipped, so none need buffering,
+/// and this is the same as `format_debug_truncated_to_head_and_tail`.
+pub fn format_debug_truncated_to_head_and_buffered_tail<D, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    head_length: usize,
+    tail_length: usize,
+) -> Result<(), std::fmt::Error>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let options = TruncationOptions::new().head_and_tail(head_length, tail_length);
+    format_debug_truncated_with_options(f, exact_into_iter, &options)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator<Item=(K, V)>>>` of `Debug` key/value pairs
+/// to `Formatter` as a map, truncating to the specified length.
+pub fn format_debug_map_truncated_to_max_length<K, V, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    max_length: usize,
+) -> Result<(), std::fmt::Error>
+where
+    K: Debug,
+    V: Debug,
+    ESI: ExactSizeIterator<Item = (K, V)>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    let options = TruncationOptions::new().max_length(max_length);
+    format_debug_map_truncated_with_options(f, exact_into_iter, &options)
+}
+
+/// Write an `IntoIterator<IntoIter=ExactSizeIterator<Item=(K, V)>>>` of `Debug` key/value pairs
+/// to `Formatter` as a map, truncating as specified by `options`.
+pub fn format_debug_map_truncated_with_options<K, V, ESI, ESII>(
+    f: &mut Formatter<'_>,
+    exact_into_iter: ESII,
+    options: &TruncationOptions,
+) -> Result<(), std::fmt::Error>
+where
+    K: Debug,
+    V: Debug,
+    ESI: ExactSizeIterator<Item = (K, V)>,
+    ESII: IntoIterator<IntoIter = ESI>,
+{
+    format_debug_shaped_truncated_with_options(
+        f,
+        exact_into_iter
+            .into_iter()
+            .map(|(key, value)| MapEntry(key, value)),
+        options,
+        ContainerShape::Set,
+    )
+}
+
+/// Arbitrary constant used to truncate number of items in debug string.
+pub const DEFAULT_DEBUG_FMT_MAX_ITEMS: usize = 42;
+
+/// Truncates the debug string of `source` to `MAX_LENGTH` items, or to the format precision if set.
+/// `source` is anything which can be cheaply iterated again each time it is formatted:
+/// a slice, a reference to a collection, or a `Clone` exact-size iterator.
+/// Note the precision is also passed on to the items, so affects e.g. `f64` items.
+pub struct TruncatedDebug<S, const MAX_LENGTH: usize = DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+    source: S,
+    options: TruncationOptions,
+}
+
+impl<S, const MAX_LENGTH: usize> TruncatedDebug<S, MAX_LENGTH> {
+    pub fn new(source: S) -> Self {
+        Self::with_options(source, TruncationOptions::new().max_length(MAX_LENGTH))
+    }
+
+    /// Truncate as specified by `options` rather than to `MAX_LENGTH`.
+    pub fn with_options(source: S, options: TruncationOptions) -> Self {
+        Self { source, options }
+    }
+}
+
+impl<'slice, D, const MAX_LENGTH: usize> TruncatedDebug<&'slice [D], MAX_LENGTH> {
+    /// Truncate anything which can be viewed as a slice, e.g. `Vec`, arrays or `Box<[D]>`.
+    pub fn from_as_ref<A: AsRef<[D]> + ?Sized>(source: &'slice A) -> Self {
+        Self::new(source.as_ref())
+    }
+}
+
+impl<S, ESI, D, const MAX_LENGTH: usize> Debug for TruncatedDebug<S, MAX_LENGTH>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    S: IntoIterator<IntoIter = ESI> + Clone,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        format_debug_truncated_with_options(f, self.source.clone(), &options)
+    }
+}
+
+/// Truncates the debug string of a `source` of key/value pairs to `MAX_LENGTH` entries,
+/// or to the format precision if set, formatting them as a map.
+pub struct TruncatedMapDebug<S, const MAX_LENGTH: usize = DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+    source: S,
+    options: TruncationOptions,
+}
+
+impl<S, const MAX_LENGTH: usize> TruncatedMapDebug<S, MAX_LENGTH> {
+    pub fn new(source: S) -> Self {
+        Self::with_options(source, TruncationOptions::new().max_length(MAX_LENGTH))
+    }
+
+    /// Truncate as specified by `options` rather than to `MAX_LENGTH`.
+    pub fn with_options(source: S, options: TruncationOptions) -> Self {
+        Self { source, options }
+    }
+}
+
+impl<S, ESI, K, V, const MAX_LENGTH: usize> Debug for TruncatedMapDebug<S, MAX_LENGTH>
+where
+    K: Debug,
+    V: Debug,
+    ESI: ExactSizeIterator<Item = (K, V)>,
+    S: IntoIterator<IntoIter = ESI> + Clone,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        format_debug_map_truncated_with_options(f, self.source.clone(), &options)
+    }
+}
+
+/// Written by `TruncatedOnceDebug` when formatted again, its source having been consumed.
+pub const ALREADY_FORMATTED_MARKER: &str = "<already formatted>";
+
+/// Truncates the debug string of `source` to `MAX_LENGTH` items, or to the format precision if set,
+/// consuming `source` the first time it is formatted, so it need not be `Clone`,
+/// e.g. `vec.into_iter().map(f)` capturing a non-`Clone` value.
+/// Formatting it again writes `ALREADY_FORMATTED_MARKER`.
+pub struct TruncatedOnceDebug<S, const MAX_LENGTH: usize = DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+    source: Cell<Option<S>>,
+    options: TruncationOptions,
+}
+
+impl<S, const MAX_LENGTH: usize> TruncatedOnceDebug<S, MAX_LENGTH> {
+    pub fn new(source: S) -> Self {
+        Self::with_options(source, TruncationOptions::new().max_length(MAX_LENGTH))
+    }
+
+    /// Truncate as specified by `options` rather than to `MAX_LENGTH`.
+    pub fn with_options(source: S, options: TruncationOptions) -> Self {
+        Self {
+            source: Cell::new(Some(source)),
+            options,
+        }
+    }
+}
+
+impl<S, ESI, D, const MAX_LENGTH: usize> Debug for TruncatedOnceDebug<S, MAX_LENGTH>
+where
+    D: Debug,
+    ESI: ExactSizeIterator<Item = D>,
+    S: IntoIterator<IntoIter = ESI>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.source.take() {
+            Some(source) => {
+                let options = self.options.for_formatter(f);
+                format_debug_truncated_with_options(f, source, &options)
+            }
+            None => f.write_str(ALREADY_FORMATTED_MARKER),
+        }
+    }
+}
+
+/// Truncates the debug string of `source` to maximum of `DEFAULT_DEBUG_FMT_MAX_ITEMS`,
+/// or to the format precision if set.
+/// ```rust
+/// use strings::debug_truncation::truncated;
+/// let data = vec![0.5, 1.5, 2.5, 3.5];
+/// assert_eq!(format!("{:?}", truncated(&data)), "[0.5, 1.5, 2.5, 3.5]");
+/// assert_eq!(format!("{:.2?}", truncated(&data)), "(length=4 truncated to 2) [0.5, 1.5]...");
+/// ```
+pub fn truncated<S>(source: S) -> TruncatedDebug<S> {
+    TruncatedDebug::new(source)
+}
+
+/// Truncates the debug string of `source` as specified by `options`,
+/// with the item limit replaced by the format precision if set.
+pub fn truncated_with_options<S>(source: S, options: TruncationOptions) -> TruncatedDebug<S> {
+    TruncatedDebug::with_options(source, options)
+}
+
+/// Truncates the debug string of `source` to maximum of `DEFAULT_DEBUG_FMT_MAX_ITEMS`,
+/// or to the format precision if set, consuming `source` the first time it is formatted.
+/// ```rust
+/// use strings::debug_truncation::truncated_once;
+/// let names = vec![String::from("a"), String::from("b")];
+/// let debug = truncated_once(names.into_iter().map(|name| name + "!"));
+/// assert_eq!(format!("{:?}", debug), "[\"a!\", \"b!\"]");
+/// assert_eq!(format!("{:?}", debug), "<already formatted>");
+/// ```
+pub fn truncated_once<S>(source: S) -> TruncatedOnceDebug<S> {
+    TruncatedOnceDebug::new(source)
+}
+
+/// Truncates the debug string of a `source` of key/value pairs to maximum of `DEFAULT_DEBUG_FMT_MAX_ITEMS`,
+/// or to the format precision if set.
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use strings::debug_truncation::truncated_map;
+/// let data = (1..=3).map(|i| (i, -i)).collect::<BTreeMap<_, _>>();
+/// assert_eq!(format!("{:.2?}", truncated_map(&data)), "(length=3 truncated to 2) {1: -1, 2: -2}...");
+/// ```
+pub fn truncated_map<S>(source: S) -> TruncatedMapDebug<S> {
+    TruncatedMapDebug::new(source)
+}
+
+/// Truncates the debug string of a `source` of key/value pairs as specified by `options`,
+/// with the item limit replaced by the format precision if set.
+pub fn truncated_map_with_options<S>(
+    source: S,
+    options: TruncationOptions,
+) -> TruncatedMapDebug<S> {
+    TruncatedMapDebug::with_options(source, options)
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_truncated_debug {
+        use super::super::TruncatedDebug;
+        use std::collections::{BTreeSet, VecDeque};
+
+        const MAX_TEST_ITEMS: usize = 3;
+
+        type TestTruncatedDebug<S> = TruncatedDebug<S, MAX_TEST_ITEMS>;
+
+        #[test]
+        fn truncated_debug_slice() {
+            let result = format!("{:?}", TestTruncatedDebug::new(&[1, 2, 3, 4][..]));
+
+            assert_eq!(result, "(length=4 truncated to 3) [1, 2, 3]...");
+        }
+
+        #[test]
+        fn truncated_debug_as_ref() {
+            let boxed_slice = vec![1, 2, 3, 4].into_boxed_slice();
+            let result = format!("{:?}", TestTruncatedDebug::from_as_ref(&boxed_slice));
+
+            assert_eq!(result, "(length=4 truncated to 3) [1, 2, 3]...");
+        }
+
+        #[test]
+        fn truncated_debug_floats() {
+            let floats = vec![0.5, 1.5, 2.5, 3.5];
+            let result = format!("{:?}", TestTruncatedDebug::new(&floats));
+
+            assert_eq!(result, "(length=4 truncated to 3) [0.5, 1.5, 2.5]...");
+        }
+
+        #[test]
+        fn truncated_debug_collection_reference() {
+            let vec_deque = (1..=3).collect::<VecDeque<_>>();
+            let result = format!("{:?}", TestTruncatedDebug::new(&vec_deque));
+
+            assert_eq!(result, "[1, 2, 3]");
+        }
+
+        #[test]
+        fn truncated_debug_clone_iterator() {
+            let set = (1..=5).collect::<BTreeSet<_>>();
+            let doubled = set.iter().map(|i| i * 2);
+            let result = format!("{:?}", TestTruncatedDebug::new(doubled));
+
+            assert_eq!(result, "(length=5 truncated to 3) [2, 4, 6]...");
+        }
+
+        #[test]
+        fn truncated_debug_default_max_length() {
+            let data = (1..=43).collect::<Vec<_>>();
+            let result = format!("{:?}", TruncatedDebug::<_>::new(&data));
+
+            assert!(
+                result.starts_with("(length=43 truncated to 42) [1, 2,"),
+                "{}",
+                result
+            );
+        }
+
+        #[test]
+        fn truncated_debug_owned_clone_iterator() {
+            let vec = (1..=5).collect::<Vec<_>>();
+            let debug = TestTruncatedDebug::new(vec.into_iter().map(|i| i * 2));
+
+            assert_eq!(
+                format!("{:?}", debug),
+                "(length=5 truncated to 3) [2, 4, 6]..."
+            );
+            assert_eq!(format!("{:.1?}", debug), "(length=5 truncated to 1) [2]...");
+        }
+    }
+
+    mod test_truncated_once_debug {
+        use super::super::{TruncatedOnceDebug, ALREADY_FORMATTED_MARKER};
+
+        const MAX_TEST_ITEMS: usize = 3;
+
+        type TestTruncatedOnceDebug<S> = TruncatedOnceDebug<S, MAX_TEST_ITEMS>;
+
+        /// Example item which is neither `Clone` nor `Copy`.
+        #[derive(Debug)]
+        struct NotClone(i32);
+
+        #[test]
+        fn truncated_once_debug_consuming_iterator() {
+            let items = (1..=5).map(NotClone).collect::<Vec<_>>();
+            let debug = TestTruncatedOnceDebug::new(items.into_iter().map(|item| item.0));
+            let result = format!("{:?}", debug);
+
+            assert_eq!(result, "(length=5 truncated to 3) [1, 2, 3]...");
+        }
+
+        #[test]
+        fn truncated_once_debug_formatted_again() {
+            let items = (1..=2).map(NotClone).collect::<Vec<_>>();
+            let debug = TestTruncatedOnceDebug::new(items);
+
+            assert_eq!(format!("{:?}", debug), "[NotClone(1), NotClone(2)]");
+            assert_eq!(format!("{:?}", debug), ALREADY_FORMATTED_MARKER);
+        }
+
+        #[test]
+        fn truncated_once_debug_precision_overrides_max_length() {
+            let debug = TestTruncatedOnceDebug::new(vec![1, 2]);
+            let result = format!("{:.1?}", debug);
+
+            assert_eq!(result, "(length=2 truncated to 1) [1]...");
+        }
+    }
+
+    mod test_truncated_debug_with_options {
+        use super::super::{truncated_map_with_options, truncated_with_options};
+        use crate::debug_truncation_options::TruncationOptions;
+        use std::collections::BTreeMap;
+
+        #[test]
+        fn truncated_debug_with_options_head_and_tail() {
+            let data = (1..=6).collect::<Vec<_>>();
+            let options = TruncationOptions::new().head_and_tail(1, 2);
+            let result = format!("{:?}", truncated_with_options(&data, options));
+
+            assert_eq!(result, "(length=6 showing 1+2) [1, ..., 5, 6]");
+        }
+
+        #[test]
+        fn truncated_debug_with_options_precision_keeps_tail() {
+            let data = (1..=6).collect::<Vec<_>>();
+            let options = TruncationOptions::new().head_and_tail(1, 2).marker("~");
+            let result = format!("{:.2?}", truncated_with_options(&data, options));
+
+            assert_eq!(result, "(length=6 showing 1+1) [1, ~, 6]");
+        }
+
+        #[test]
+        fn truncated_debug_with_options_precision_overrides_max_length() {
+            let data = (1..=6).collect::<Vec<_>>();
+            let options = TruncationOptions::new().max_length(5).marker("~");
+            let result = format!("{:.2?}", truncated_with_options(&data, options));
+
+            assert_eq!(result, "(length=6 truncated to 2) [1, 2]~");
+        }
+
+        #[test]
+        fn truncated_debug_with_options_precision_not_passed_to_items() {
+            let data = [0.5, 1.5, 2.5];
+            let options = TruncationOptions::new();
+            let result = format!("{:.2?}", truncated_with_options(&data, options.clone()));
+            let capped = format!(
+                "{:.2?}",
+                truncated_with_options(&data, options.max_element_chars(3))
+            );
+
+            assert_eq!(result, "(length=3 truncated to 2) [0.5, 1.5]...");
+            assert_eq!(capped, "(length=3 truncated to 2) [0.5, 1.5]...");
+        }
+
+        #[test]
+        fn truncated_map_debug_with_options_head_and_tail() {
+            let map = (1..=4).map(|i| (i, -i)).collect::<BTreeMap<_, _>>();
+            let options = TruncationOptions::new().head_and_tail(1, 1);
+            let result = format!("{:?}", truncated_map_with_options(&map, options));
+
+            assert_eq!(result, "(length=4 showing 1+1) {1: -1, ..., 4: -4}");
+        }
+    }
+
+    mod test_truncated_map_debug {
+        use super::super::TruncatedMapDebug;
+        use std::collections::BTreeMap;
+
+        #[test]
+        fn truncated_map_debug_btree_map() {
+            let map = (1..=3).map(|i| (i, -i)).collect::<BTreeMap<_, _>>();
+            let result = format!("{:?}", TruncatedMapDebug::<_, 2>::new(&map));
+
+            assert_eq!(result, "(length=3 truncated to 2) {1: -1, 2: -2}...");
+        }
+
+        #[test]
+        fn truncated_map_debug_zipped_iterator() {
+            let keys = ["a", "b"];
+            let values = [1.5, 2.5];
+            let zipped = keys.iter().zip(values.iter());
+            let result = format!("{:?}", TruncatedMapDebug::<_, 2>::new(zipped));
+
+            assert_eq!(result, r#"{"a": 1.5, "b": 2.5}"#);
+        }
+    }
+}
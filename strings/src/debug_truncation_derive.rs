@@ -0,0 +1,280 @@
+use crate::debug_native_shape_truncation::NativeShapeTruncateDebug;
+use crate::debug_truncation_options::TruncationOptions;
+use std::fmt::{Debug, Formatter};
+
+/// Derives `Debug`, truncating the collection fields marked with `#[truncate(...)]`
+/// as `NativeShapeHolder` does, and writing every other field as `#[derive(Debug)]` would.
+/// As with `NativeShapeHolder` a format precision, e.g. `{:.5?}`, replaces the limits given by the attribute.
+/// ```rust
+/// use strings::debug_truncation_derive::TruncatedDebug;
+/// #[derive(TruncatedDebug)]
+/// struct StructWithVecs {
+///     name: &'static str,
+///     #[truncate(max = 2)]
+///     first: Vec<i32>,
+///     #[truncate(head = 1, tail = 1)]
+///     second: Vec<i32>,
+///     #[truncate(count_only)]
+///     third: Vec<i32>,
+///     #[truncate(skip)]
+///     _hidden: Vec<i32>,
+/// }
+/// let data = StructWithVecs {
+///     name: "vecs",
+///     first: vec![1, 2, 3],
+///     second: vec![1, 2, 3],
+///     third: vec![1, 2, 3],
+///     _hidden: vec![1, 2, 3],
+/// };
+/// assert_eq!(
+///     format!("{:?}", data),
+///     "StructWithVecs { name: \"vecs\", \
+///      first: (length=3 truncated to 2) [1, 2]..., \
+///      second: (length=3 showing 1+1) [1, ..., 3], \
+///      third: (length=3) }"
+/// );
+/// ```
+/// Only structs are supported:
+/// ```compile_fail
+/// use strings::debug_truncation_derive::TruncatedDebug;
+/// #[derive(TruncatedDebug)]
+/// enum Enum {
+///     A(#[truncate] Vec<i32>),
+/// }
+/// ```
+pub use strings_derive::TruncatedDebug;
+
+/// Field truncated as its `#[truncate(...)]` attribute says, or to the format precision if set.
+#[doc(hidden)]
+pub struct TruncatedField<'collection, C: ?Sized> {
+    collection: &'collection C,
+    options: TruncationOptions,
+}
+
+impl<'collection, C: ?Sized> TruncatedField<'collection, C> {
+    pub fn new(collection: &'collection C, options: TruncationOptions) -> Self {
+        Self {
+            collection,
+            options,
+        }
+    }
+}
+
+impl<'collection, C> Debug for TruncatedField<'collection, C>
+where
+    C: NativeShapeTruncateDebug + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let options = self.options.for_formatter(f);
+        self.collection
+            .format_debug_native_truncated_with_options(f, &options)
+    }
+}
+
+/// Field marked `#[truncate(count_only)]`, written only as `(length=N)` so no item is ever formatted.
+#[doc(hidden)]
+pub struct CountOnlyField<'collection, C: ?Sized>(pub &'collection C);
+
+impl<'collection, C> Debug for CountOnlyField<'collection, C>
+where
+    C: NativeShapeTruncateDebug + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "(length={})", self.0.native_length())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_derive_truncated_debug {
+        use super::super::TruncatedDebug;
+        use linked_hash_set::LinkedHashSet;
+        use std::collections::BTreeMap;
+        use std::fmt::Debug;
+
+        #[derive(TruncatedDebug)]
+        struct StructWithASlice<'slice> {
+            #[truncate(max = 2)]
+            slice: &'slice [i32],
+        }
+
+        #[derive(TruncatedDebug)]
+        struct StructWithAHashSet {
+            #[truncate(max = 2)]
+            hash_set: LinkedHashSet<i32>,
+        }
+
+        #[derive(TruncatedDebug)]
+        struct StructWithAMap {
+            #[truncate(head = 1, tail = 1)]
+            map: BTreeMap<i32, &'static str>,
+        }
+
+        #[derive(TruncatedDebug)]
+        struct StructWithLengths {
+            #[truncate(count_only)]
+            counted: Vec<i32>,
+            #[truncate(skip)]
+            _skipped: Vec<i32>,
+            untruncated: Vec<i32>,
+        }
+
+        #[derive(TruncatedDebug)]
+        struct TupleStructWithAVec(&'static str, #[truncate] Vec<i32>);
+
+        #[derive(TruncatedDebug)]
+        struct GenericStructWithAVec<T> {
+            #[truncate(max = 1)]
+            vec: Vec<T>,
+        }
+
+        #[derive(TruncatedDebug)]
+        struct UnitStruct;
+
+        #[derive(TruncatedDebug)]
+        struct StructWithASecret {
+            #[truncate(count_only)]
+            secret: Vec<i32>,
+            #[truncate(max = 2)]
+            items: Vec<i32>,
+        }
+
+        fn truncate_debug_string_for<T: Debug>(input: T) -> String {
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn derived_slice_field_truncated_to_max() {
+            let result = truncate_debug_string_for(StructWithASlice { slice: &[1, 2, 3] });
+
+            assert_eq!(
+                result,
+                "StructWithASlice { slice: (length=3 truncated to 2) [1, 2]... }"
+            );
+        }
+
+        #[test]
+        fn derived_linked_hash_set_field_keeps_set_shape() {
+            let result = truncate_debug_string_for(StructWithAHashSet {
+                hash_set: (1..=3).collect(),
+            });
+
+            assert_eq!(
+                result,
+                "StructWithAHashSet { hash_set: (length=3 truncated to 2) {1, 2}... }"
+            );
+        }
+
+        #[test]
+        fn derived_map_field_truncated_to_head_and_tail() {
+            let result = truncate_debug_string_for(StructWithAMap {
+                map: vec![(1, "a"), (2, "b"), (3, "c")].into_iter().collect(),
+            });
+
+            assert_eq!(
+                result,
+                "StructWithAMap { map: (length=3 showing 1+1) {1: \"a\", ..., 3: \"c\"} }"
+            );
+        }
+
+        #[test]
+        fn derived_count_only_and_skipped_fields() {
+            let result = truncate_debug_string_for(StructWithLengths {
+                counted: vec![1, 2, 3],
+                _skipped: vec![4, 5, 6],
+                untruncated: vec![7, 8, 9],
+            });
+
+            assert_eq!(
+                result,
+                "StructWithLengths { counted: (length=3), untruncated: [7, 8, 9] }"
+            );
+        }
+
+        #[test]
+        fn derived_tuple_struct_truncated_to_default_max() {
+            let result = truncate_debug_string_for(TupleStructWithAVec("vec", (1..=50).collect()));
+
+            assert!(
+                result
+                    .starts_with("TupleStructWithAVec(\"vec\", (length=50 truncated to 42) [1, 2,"),
+                "{}",
+                result
+            );
+            assert!(result.ends_with(", 42]...)"), "{}", result);
+        }
+
+        #[test]
+        fn derived_generic_struct_truncated_to_max() {
+            let result = truncate_debug_string_for(GenericStructWithAVec {
+                vec: vec!["a", "b"],
+            });
+
+            assert_eq!(
+                result,
+                "GenericStructWithAVec { vec: (length=2 truncated to 1) [\"a\"]... }"
+            );
+        }
+
+        #[test]
+        fn derived_struct_pretty_printed_when_not_truncated() {
+            let result = format!("{:#?}", StructWithASlice { slice: &[1, 2] });
+
+            assert_eq!(
+                result,
+                "StructWithASlice {\n    slice: [\n        1,\n        2,\n    ],\n}"
+            );
+        }
+
+        #[test]
+        fn derived_fields_truncated_to_precision() {
+            let input = StructWithASecret {
+                secret: vec![1, 2, 3, 4, 5],
+                items: vec![1, 2, 3, 4, 5],
+            };
+            let result = format!("{:.3?}", input);
+
+            assert_eq!(
+                result,
+                "StructWithASecret { secret: (length=5), \
+                 items: (length=5 truncated to 3) [1, 2, 3]... }"
+            );
+        }
+
+        #[test]
+        fn derived_fields_keep_tail_under_precision() {
+            let input = StructWithAMap {
+                map: (1..=5).map(|i| (i, "x")).collect(),
+            };
+            let result = format!("{:.2?}", input);
+
+            assert_eq!(
+                result,
+                "StructWithAMap { map: (length=5 showing 1+1) {1: \"x\", ..., 5: \"x\"} }"
+            );
+        }
+
+        #[test]
+        fn derived_count_only_empty_field() {
+            let input = StructWithASecret {
+                secret: vec![],
+                items: vec![],
+            };
+            let result = format!("{:?}", input);
+
+            assert_eq!(
+                result,
+                "StructWithASecret { secret: (length=0), items: [] }"
+            );
+        }
+
+        #[test]
+        fn derived_unit_struct() {
+            let result = truncate_debug_string_for(UnitStruct);
+
+            assert_eq!(result, "UnitStruct");
+        }
+    }
+}
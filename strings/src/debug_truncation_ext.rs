@@ -0,0 +1,135 @@
+use crate::debug_native_shape_truncation::{NativeShapeHolder, NativeShapeTruncateDebug};
+use crate::debug_truncation::{TruncatedDebug, DEFAULT_DEBUG_FMT_MAX_ITEMS};
+use crate::debug_truncation_options::TruncationOptions;
+
+/// Truncating `Debug` for any slice, array or std collection, including `LinkedHashSet` and maps,
+/// keeping the collection's own delimiters, as `NativeShapeHolder` does.
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use strings::prelude::*;
+/// let map = (1..=3).map(|i| (i, -i)).collect::<BTreeMap<_, _>>();
+/// assert_eq!(format!("{:?}", map.truncated_debug_to::<2>()), "(length=3 truncated to 2) {1: -1, 2: -2}...");
+/// let options = TruncationOptions::new().head_and_tail(1, 1);
+/// assert_eq!(format!("{:?}", [1, 2, 3].truncated_debug_with(&options)), "(length=3 showing 1+1) [1, ..., 3]");
+/// ```
+pub trait TruncatedDebugExt: NativeShapeTruncateDebug {
+    /// Truncate to `DEFAULT_DEBUG_FMT_MAX_ITEMS` items, or to the format precision if set.
+    fn truncated_debug(&self) -> NativeShapeHolder<'_, Self, DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+        NativeShapeHolder::new(self)
+    }
+
+    /// Truncate to `MAX_LENGTH` items, or to the format precision if set.
+    fn truncated_debug_to<const MAX_LENGTH: usize>(
+        &self,
+    ) -> NativeShapeHolder<'_, Self, MAX_LENGTH> {
+        NativeShapeHolder::new(self)
+    }
+
+    /// Truncate as specified by `options`, with the item limit replaced by the format precision if set.
+    fn truncated_debug_with(
+        &self,
+        options: &TruncationOptions,
+    ) -> NativeShapeHolder<'_, Self, DEFAULT_DEBUG_FMT_MAX_ITEMS> {
+        NativeShapeHolder::with_options(self, options.clone())
+    }
+}
+
+impl<C: NativeShapeTruncateDebug + ?Sized> TruncatedDebugExt for C {}
+
+/// Truncating `Debug` for `Clone` exact-size iterators, e.g. `set.iter().map(f)`, as `TruncatedDebug` does.
+/// ```rust
+/// use std::collections::BTreeSet;
+/// use strings::prelude::*;
+/// let set = (1..=5).collect::<BTreeSet<_>>();
+/// let squares = set.iter().map(|i| i * i).truncated_debug_to::<3>();
+/// assert_eq!(format!("{:?}", squares), "(length=5 truncated to 3) [1, 4, 9]...");
+/// ```
+pub trait TruncatedDebugIteratorExt: ExactSizeIterator + Clone {
+    /// Truncate to `DEFAULT_DEBUG_FMT_MAX_ITEMS` items, or to the format precision if set.
+    fn truncated_debug(self) -> TruncatedDebug<Self> {
+        TruncatedDebug::new(self)
+    }
+
+    /// Truncate to `MAX_LENGTH` items, or to the format precision if set.
+    fn truncated_debug_to<const MAX_LENGTH: usize>(self) -> TruncatedDebug<Self, MAX_LENGTH> {
+        TruncatedDebug::new(self)
+    }
+
+    /// Truncate as specified by `options`, with the item limit replaced by the format precision if set.
+    fn truncated_debug_with(self, options: &TruncationOptions) -> TruncatedDebug<Self> {
+        TruncatedDebug::with_options(self, options.clone())
+    }
+}
+
+impl<I: ExactSizeIterator + Clone> TruncatedDebugIteratorExt for I {}
+
+#[cfg(test)]
+mod tests {
+    mod test_truncated_debug_ext {
+        use crate::prelude::*;
+        use linked_hash_set::LinkedHashSet;
+        use std::collections::{HashMap, VecDeque};
+        use std::fmt::{Debug, Formatter};
+
+        /// Example struct which truncates its fields with the extension traits.
+        struct StructWithCollections<'slice> {
+            slice: &'slice [i32],
+            hash_set: LinkedHashSet<i32>,
+            vec_deque: VecDeque<i32>,
+        }
+
+        impl<'slice> Debug for StructWithCollections<'slice> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                f.debug_struct("StructWithCollections")
+                    .field("slice", &self.slice.truncated_debug_to::<2>())
+                    .field("hash_set", &self.hash_set.truncated_debug_to::<1>())
+                    .field("vec_deque", &self.vec_deque.truncated_debug())
+                    .finish()
+            }
+        }
+
+        #[test]
+        fn truncated_debug_ext_struct_fields() {
+            let input = StructWithCollections {
+                slice: &[1, 2, 3],
+                hash_set: (1..=3).collect(),
+                vec_deque: (1..=3).collect(),
+            };
+            let result = format!("{:?}", input);
+
+            assert_eq!(
+                result,
+                "StructWithCollections { \
+                 slice: (length=3 truncated to 2) [1, 2]..., \
+                 hash_set: (length=3 truncated to 1) {1}..., \
+                 vec_deque: [1, 2, 3] }"
+            );
+        }
+
+        #[test]
+        fn truncated_debug_ext_vec_precision_overrides_max_length() {
+            let vec = vec![1, 2, 3];
+            let result = format!("{:.1?}", vec.truncated_debug());
+
+            assert_eq!(result, "(length=3 truncated to 1) [1]...");
+        }
+
+        #[test]
+        fn truncated_debug_ext_hash_map_with_options() {
+            let map = vec![(1, "a")].into_iter().collect::<HashMap<_, _>>();
+            let options = TruncationOptions::new().max_length(0);
+            let result = format!("{:?}", map.truncated_debug_with(&options));
+
+            assert_eq!(result, "(length=1)");
+        }
+
+        #[test]
+        fn truncated_debug_iterator_ext() {
+            let array = [1, 2, 3];
+            let options = TruncationOptions::new().max_length(1).marker("~");
+            let result = format!("{:?}", array.iter().rev().truncated_debug_with(&options));
+
+            assert_eq!(result, "(length=3 truncated to 1) [3]~");
+        }
+    }
+}
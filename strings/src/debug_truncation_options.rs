@@ -0,0 +1,600 @@
+use crate::debug_slice_truncation::DEFAULT_SLICE_DEBUG_FMT_MAX_ITEMS;
+use crate::debug_truncation::DEFAULT_DEBUG_FMT_MAX_ITEMS;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter, Write};
+
+/// What is written in place of the items when a non-empty collection is truncated to zero items.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZeroLengthDisplay {
+    /// Only the length, e.g. `(length=5)`.
+    Length,
+    /// Only the truncation marker, e.g. `...`.
+    Marker,
+    /// The header, if shown, followed by empty delimiters and the truncation marker, e.g. `(length=5 truncated to 0) []...`.
+    EmptyContainer,
+}
+
+/// How items are picked when sampling, rather than showing the first ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sampling {
+    /// Every k-th item from the first, with k chosen so at most the item limit are shown.
+    Stride,
+    /// A random subset picked by reservoir sampling, the same every time for the same `seed`.
+    Reservoir { seed: u64 },
+}
+
+/// Length written in the `(length...)` header, which may only be a lower bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ReportedLength {
+    /// Written as `=N`.
+    Exact(usize),
+    /// Written as `≥N`.
+    AtLeast(usize),
+    /// Written as `=N in R runs`, for `length` elements collapsed into `runs` runs of equal elements.
+    Runs { length: usize, runs: usize },
+}
+
+impl Display for ReportedLength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ReportedLength::Exact(length) => write!(f, "={}", length),
+            ReportedLength::AtLeast(length) => write!(f, "≥{}", length),
+            ReportedLength::Runs { length, runs } => write!(f, "={} in {} runs", length, runs),
+        }
+    }
+}
+
+/// Settings shared by every truncation entry point.
+/// ```rust
+/// use strings::debug_truncation::truncated_with_options;
+/// use strings::debug_truncation_options::TruncationOptions;
+/// let options = TruncationOptions::new().head_and_tail(2, 1).marker("…");
+/// let data = (1..=10).collect::<Vec<_>>();
+/// assert_eq!(
+///     format!("{:?}", truncated_with_options(&data, options)),
+///     "(length=10 showing 2+1) [1, 2, …, 10]"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TruncationOptions {
+    pub(crate) head_length: usize,
+    pub(crate) tail_length: usize,
+    /// Whether the marker goes between the head and a tail, possibly empty, set by `head_and_tail`.
+    pub(crate) head_and_tail: bool,
+    pub(crate) marker: Cow<'static, str>,
+    pub(crate) show_header: bool,
+    pub(crate) max_element_chars: Option<usize>,
+    pub(crate) zero_length: ZeroLengthDisplay,
+    pub(crate) max_total_chars: Option<usize>,
+    pub(crate) split_last_element: bool,
+    pub(crate) sampling: Option<Sampling>,
+    /// Whether the format precision was taken as the item limit, so is not passed on to the items.
+    pub(crate) strip_item_precision: bool,
+}
+
+impl TruncationOptions {
+    /// Options reproducing the output of `format_debug_exact_size_truncated_to_max_length`
+    /// with `DEFAULT_DEBUG_FMT_MAX_ITEMS`.
+    pub fn new() -> Self {
+        Self {
+            head_length: DEFAULT_DEBUG_FMT_MAX_ITEMS,
+            tail_length: 0,
+            head_and_tail: false,
+            marker: Cow::Borrowed("..."),
+            show_header: true,
+            max_element_chars: None,
+            zero_length: ZeroLengthDisplay::Length,
+            max_total_chars: None,
+            split_last_element: false,
+            sampling: None,
+            strip_item_precision: false,
+        }
+    }
+
+    /// Preset reproducing `debug_slice_truncation::truncated_debug`.
+    pub fn slice_preset() -> Self {
+        Self::new().max_length(DEFAULT_SLICE_DEBUG_FMT_MAX_ITEMS)
+    }
+
+    /// Preset reproducing `debug_exact_size_truncation::truncated_exact_size_debug_fmt`.
+    pub fn exact_size_preset() -> Self {
+        Self::new()
+    }
+
+    /// Show at most the first `max_length` items.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.head_length = max_length;
+        self.tail_length = 0;
+        self.head_and_tail = false;
+        self
+    }
+
+    /// Show at most the first `head_length` and the last `tail_length` items,
+    /// with the marker between them, as `format_debug_slice_truncated_to_head_and_tail` does,
+    /// even with no tail, e.g. `(length=5 showing 2+0) [1, 2, ...]`.
+    pub fn head_and_tail(mut self, head_length: usize, tail_length: usize) -> Self {
+        self.head_length = head_length;
+        self.tail_length = tail_length;
+        self.head_and_tail = true;
+        self
+    }
+
+    /// Text written where items were omitted, `...` by default.
+    pub fn marker(mut self, marker: impl Into<Cow<'static, str>>) -> Self {
+        self.marker = marker.into();
+        self
+    }
+
+    /// Whether to write the `(length=...)` header before truncated items, `true` by default.
+    pub fn show_header(mut self, show_header: bool) -> Self {
+        self.show_header = show_header;
+        self
+    }
+
+    /// Cap each item's `Debug` output to `max_element_chars` chars, unlimited by default.
+    /// Capped items are marked with the number of chars cut, e.g. `123…(+2 chars)`,
+    /// and a cut string keeps its closing quote, e.g. `"abc…"(+9812 chars)`.
+    /// Capped items are written with the format's flags, e.g. `{:x?}`, and capped after.
+    pub fn max_element_chars(mut self, max_element_chars: usize) -> Self {
+        self.max_element_chars = Some(max_element_chars);
+        self
+    }
+
+    /// What to write when a non-empty collection is truncated to zero items,
+    /// `ZeroLengthDisplay::Length` by default.
+    pub fn zero_length(mut self, zero_length: ZeroLengthDisplay) -> Self {
+        self.zero_length = zero_length;
+        self
+    }
+
+    /// Show only as many whole items as fit within `max_total_chars` chars of item `Debug` output,
+    /// not counting delimiters and separators, unlimited by default.
+    /// Items are taken from the front up to the head length, then from the back up to the tail length,
+    /// and if any does not fit this is reported as e.g. `(length=100, 7 shown within 2000 chars)`.
+    /// Items are measured as written, with the format's flags, e.g. `{:x?}`.
+    /// Not applied to sampled items, see `sampling`.
+    pub fn max_total_chars(mut self, max_total_chars: usize) -> Self {
+        self.max_total_chars = Some(max_total_chars);
+        self
+    }
+
+    /// Whether to fill what is left of `max_total_chars` with the start of the first item which
+    /// does not fit, marked as `max_element_chars` marks capped items, `false` by default.
+    pub fn split_last_element(mut self, split_last_element: bool) -> Self {
+        self.split_last_element = split_last_element;
+        self
+    }
+
+    /// Show items picked from the whole collection by `sampling`, each with its index,
+    /// e.g. `(length=10000 sampled 10 @stride 1000) [0: a, 1000: b, ..]...`, rather than the first ones.
+    /// As many items are sampled as the item limit, including any tail, would show.
+    /// Sampled items are written whole, so `max_total_chars` and `split_last_element` are not applied
+    /// with it, though `max_element_chars` is.
+    /// Only the exact-size formatters sample; the plain iterator formatters, which do not know
+    /// the length up front, show the first items as without it.
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = Some(sampling);
+        self
+    }
+
+    /// Total number of items shown from the head and the tail.
+    pub(crate) fn shown_length(&self) -> usize {
+        self.head_length.saturating_add(self.tail_length)
+    }
+
+    /// Whether a tail is shown after the marker, rather than the marker closing the items.
+    pub(crate) fn shows_tail(&self) -> bool {
+        self.head_and_tail || self.tail_length > 0
+    }
+
+    /// These options, with the item limit replaced by the format precision, e.g. `{:.10?}`, if set.
+    /// The precision is then not passed on to the items, so `{:.2?}` of `[0.5]` is `[0.5]`.
+    /// A tail is kept, taking at most half of the precision, so `head_and_tail(5, 2)` shows
+    /// the first 2 and the last item for `{:.3?}`.
+    pub(crate) fn for_formatter(&self, f: &Formatter<'_>) -> Cow<'_, Self> {
+        match f.precision() {
+            Some(precision) => {
+                let mut options = if self.shows_tail() {
+                    let tail_length = self.tail_length.min(precision / 2);
+                    self.clone()
+                        .head_and_tail(precision - tail_length, tail_length)
+                } else {
+                    self.clone().max_length(precision)
+                };
+                options.strip_item_precision = true;
+                Cow::Owned(options)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+
+    /// Write the `(length=...)` header for `length` items, if enabled.
+    pub(crate) fn format_header<W: Write>(
+        &self,
+        f: &mut W,
+        length: ReportedLength,
+    ) -> Result<(), std::fmt::Error> {
+        if !self.show_header {
+            Ok(())
+        } else if !self.shows_tail() {
+            write!(f, "(length{} truncated to {}) ", length, self.head_length)
+        } else {
+            write!(
+                f,
+                "(length{} showing {}+{}) ",
+                length, self.head_length, self.tail_length
+            )
+        }
+    }
+
+    /// Write the `(length=N, M shown within C chars)` header used when `max_total_chars` is reached, if enabled.
+    pub(crate) fn format_char_budget_header(
+        &self,
+        f: &mut Formatter<'_>,
+        length: ReportedLength,
+        shown_length: usize,
+        max_total_chars: usize,
+    ) -> Result<(), std::fmt::Error> {
+        if self.show_header {
+            write!(
+                f,
+                "(length{}, {} shown within {} chars) ",
+                length, shown_length, max_total_chars
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write the `(length=N, M matching, K shown)` header used when showing only matching items, if enabled.
+    pub(crate) fn format_filtered_header(
+        &self,
+        f: &mut Formatter<'_>,
+        length: ReportedLength,
+        matching_length: usize,
+        shown_length: usize,
+    ) -> Result<(), std::fmt::Error> {
+        if self.show_header {
+            write!(
+                f,
+                "(length{}, {} matching, {} shown) ",
+                length, matching_length, shown_length
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write the `(length=N top K by key)` header used when showing the items with the largest or
+    /// smallest keys, labelled by `order`, if enabled.
+    pub(crate) fn format_ordered_header(
+        &self,
+        f: &mut Formatter<'_>,
+        length: ReportedLength,
+        order: &str,
+        shown_length: usize,
+    ) -> Result<(), std::fmt::Error> {
+        if self.show_header {
+            write!(f, "(length{} {} {} by key) ", length, order, shown_length)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write the `(length=N sampled M @strategy)` header used when sampling, if enabled.
+    pub(crate) fn format_sampled_header(
+        &self,
+        f: &mut Formatter<'_>,
+        length: ReportedLength,
+        shown_length: usize,
+        strategy: std::fmt::Arguments<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        if self.show_header {
+            write!(
+                f,
+                "(length{} sampled {} {}) ",
+                length, shown_length, strategy
+            )
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for TruncationOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_truncation_options {
+        use super::super::{Sampling, TruncationOptions, ZeroLengthDisplay};
+        use crate::debug_truncation::{format_debug_truncated_with_options, TruncatedDebug};
+        use std::fmt::{Debug, Formatter};
+
+        struct StructWithAVec {
+            vec: Vec<i32>,
+            options: TruncationOptions,
+        }
+
+        impl Debug for StructWithAVec {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+                format_debug_truncated_with_options(f, &self.vec, &self.options)
+            }
+        }
+
+        fn truncate_debug_string_for(inputs: &[i32], options: TruncationOptions) -> String {
+            let input = StructWithAVec {
+                vec: inputs.to_vec(),
+                options,
+            };
+            let result = format!("{:?}", input);
+
+            result
+        }
+
+        #[test]
+        fn truncation_options_max_length() {
+            let options = TruncationOptions::new().max_length(4);
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], options);
+
+            assert_eq!(result, "(length=5 truncated to 4) [1, 2, 3, 4]...");
+        }
+
+        #[test]
+        fn truncation_options_head_and_tail() {
+            let options = TruncationOptions::new().head_and_tail(2, 2);
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], options);
+
+            assert_eq!(result, "(length=5 showing 2+2) [1, 2, ..., 4, 5]");
+        }
+
+        #[test]
+        fn truncation_options_head_and_tail_saturating() {
+            let options = TruncationOptions::new().head_and_tail(usize::MAX, 1);
+            let result = truncate_debug_string_for(&[1, 2, 3], options);
+
+            assert_eq!(result, "[1, 2, 3]");
+        }
+
+        #[test]
+        fn truncation_options_marker() {
+            let options = TruncationOptions::new().max_length(2).marker(" [more]");
+            let result = truncate_debug_string_for(&[1, 2, 3], options);
+
+            assert_eq!(result, "(length=3 truncated to 2) [1, 2] [more]");
+        }
+
+        #[test]
+        fn truncation_options_head_and_empty_tail() {
+            let options = TruncationOptions::new().head_and_tail(2, 0).marker("~");
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5], options);
+
+            assert_eq!(result, "(length=5 showing 2+0) [1, 2, ~]");
+        }
+
+        #[test]
+        fn truncation_options_without_header() {
+            let options = TruncationOptions::new().max_length(2).show_header(false);
+            let result = truncate_debug_string_for(&[1, 2, 3], options);
+
+            assert_eq!(result, "[1, 2]...");
+        }
+
+        #[test]
+        fn truncation_options_max_element_chars() {
+            let options = TruncationOptions::new().max_element_chars(3);
+            let result = truncate_debug_string_for(&[1, 12345, 123], options);
+
+            assert_eq!(result, "[1, 123…(+2 chars), 123]");
+        }
+
+        #[test]
+        fn truncation_options_max_element_chars_cut_after_backslash() {
+            let options = TruncationOptions::new().max_element_chars(3);
+            let input = TruncatedDebug::<_, 0>::with_options(&["a\"b", "a\"", "a\\"], options);
+            let result = format!("{:?}", input);
+
+            assert_eq!(
+                result,
+                "[\"a…\"(+3 chars), \"a…\"(+2 chars), \"a…\"(+2 chars)]"
+            );
+        }
+
+        #[test]
+        fn truncation_options_max_element_chars_keeps_flags() {
+            let options = TruncationOptions::new().max_element_chars(3);
+            let input = TruncatedDebug::<_, 0>::with_options(&[255, 4096], options);
+
+            assert_eq!(format!("{:x?}", input), "[ff, 100…(+1 chars)]");
+            assert_eq!(format!("{:4?}", input), "[ 25…(+1 chars), 409…(+1 chars)]");
+        }
+
+        #[test]
+        fn truncation_options_max_element_chars_escaped_quote() {
+            let options = TruncationOptions::new().max_element_chars(4);
+            let input = TruncatedDebug::<_, 0>::with_options(&["a\"bcd"], options);
+            let result = format!("{:?}", input);
+
+            assert_eq!(result, "[\"a\\\"…\"(+3 chars)]");
+        }
+
+        #[test]
+        fn truncation_options_sampling_by_stride_without_header() {
+            let options = TruncationOptions::new()
+                .max_length(2)
+                .show_header(false)
+                .marker("~")
+                .sampling(Sampling::Stride);
+            let result = truncate_debug_string_for(&[1, 2, 3, 4], options);
+
+            assert_eq!(result, "[0: 1, 2: 3]~");
+        }
+
+        #[test]
+        fn truncation_options_sampling_with_max_element_chars() {
+            let options = TruncationOptions::new()
+                .max_length(1)
+                .max_element_chars(2)
+                .sampling(Sampling::Reservoir { seed: 7 });
+            let result = truncate_debug_string_for(&[1234, 1234], options);
+
+            assert!(result.ends_with(": 12…(+2 chars)]..."), "{}", result);
+        }
+
+        #[test]
+        fn truncation_options_zero_length_marker() {
+            let options = TruncationOptions::new()
+                .max_length(0)
+                .zero_length(ZeroLengthDisplay::Marker);
+            let result = truncate_debug_string_for(&[1, 2, 3], options);
+
+            assert_eq!(result, "...");
+        }
+
+        #[test]
+        fn truncation_options_zero_length_empty_container() {
+            let options = TruncationOptions::new()
+                .max_length(0)
+                .zero_length(ZeroLengthDisplay::EmptyContainer);
+            let result = truncate_debug_string_for(&[1, 2, 3], options);
+
+            assert_eq!(result, "(length=3 truncated to 0) []...");
+        }
+
+        #[test]
+        fn truncation_options_zero_length_empty_input() {
+            let options = TruncationOptions::new()
+                .max_length(0)
+                .zero_length(ZeroLengthDisplay::Marker);
+            let result = truncate_debug_string_for(&[], options);
+
+            assert_eq!(result, "[]");
+        }
+
+        #[test]
+        fn truncation_options_max_total_chars_within_max_length() {
+            let options = TruncationOptions::new().max_length(2).max_total_chars(100);
+            let result = truncate_debug_string_for(&[1, 2, 3], options);
+
+            assert_eq!(result, "(length=3 truncated to 2) [1, 2]...");
+        }
+
+        #[test]
+        fn truncation_options_max_total_chars_split_last_element() {
+            let options = TruncationOptions::new()
+                .max_total_chars(5)
+                .split_last_element(true);
+            let result = truncate_debug_string_for(&[12, 34567, 8], options);
+
+            assert_eq!(
+                result,
+                "(length=3, 2 shown within 5 chars) [12, 345…(+2 chars)]..."
+            );
+        }
+
+        #[test]
+        fn truncation_options_max_total_chars_split_last_string_element() {
+            let options = TruncationOptions::new()
+                .max_total_chars(5)
+                .split_last_element(true);
+            let input = TruncatedDebug::<_, 0>::with_options(&["ab", "cdefgh"], options);
+            let result = format!("{:?}", input);
+
+            assert_eq!(result, "(length=2, 1 shown within 5 chars) [\"ab\"]...");
+        }
+
+        #[test]
+        fn truncation_options_max_total_chars_split_last_string_element_content() {
+            let options = TruncationOptions::new()
+                .max_total_chars(7)
+                .split_last_element(true);
+            let input = TruncatedDebug::<_, 0>::with_options(&["ab", "cdefgh"], options);
+            let result = format!("{:?}", input);
+
+            assert_eq!(
+                result,
+                "(length=2, 2 shown within 7 chars) [\"ab\", \"cd…\"(+4 chars)]..."
+            );
+        }
+
+        #[test]
+        fn truncation_options_max_total_chars_without_header() {
+            let options = TruncationOptions::new()
+                .max_total_chars(3)
+                .show_header(false);
+            let result = truncate_debug_string_for(&[1, 2, 3, 4], options);
+
+            assert_eq!(result, "[1, 2, 3]...");
+        }
+
+        #[test]
+        fn truncation_options_max_total_chars_with_tail() {
+            let options = TruncationOptions::new()
+                .head_and_tail(3, 2)
+                .max_total_chars(5);
+            let result = truncate_debug_string_for(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], options);
+
+            assert_eq!(
+                result,
+                "(length=10, 4 shown within 5 chars) [1, 2, 3, ..., 10]"
+            );
+        }
+
+        #[test]
+        fn truncation_options_max_total_chars_keeps_flags() {
+            let options = TruncationOptions::new().max_total_chars(10);
+            let input = TruncatedDebug::<_, 0>::with_options(&[1, 2, 3, 4], options.clone());
+            let hex_input = TruncatedDebug::<_, 0>::with_options(
+                &[10, 11, 255, 4096],
+                options.max_total_chars(4),
+            );
+
+            assert_eq!(
+                format!("{:3?}", input),
+                "(length=4, 3 shown within 10 chars) [  1,   2,   3]..."
+            );
+            assert_eq!(
+                format!("{:x?}", hex_input),
+                "(length=4, 3 shown within 4 chars) [a, b, ff]..."
+            );
+        }
+
+        #[test]
+        fn truncation_options_max_total_chars_measures_hex() {
+            let options = TruncationOptions::new().max_total_chars(8);
+            let input = TruncatedDebug::<_, 0>::with_options(&[-1i32, 2], options);
+
+            assert_eq!(
+                format!("{:x?}", input),
+                "(length=2, 1 shown within 8 chars) [ffffffff]..."
+            );
+            assert_eq!(
+                format!("{:+08?}", input),
+                "(length=2, 1 shown within 8 chars) [-0000001]..."
+            );
+        }
+
+        #[test]
+        fn truncation_options_presets_match_defaults() {
+            let inputs = (1..=50).collect::<Vec<_>>();
+
+            assert_eq!(
+                truncate_debug_string_for(&inputs, TruncationOptions::slice_preset()),
+                format!(
+                    "{:?}",
+                    crate::debug_slice_truncation::truncated_debug(&inputs)
+                )
+            );
+            assert_eq!(
+                truncate_debug_string_for(&inputs, TruncationOptions::exact_size_preset()),
+                format!(
+                    "{:?}",
+                    crate::debug_exact_size_truncation::truncated_exact_size_debug_fmt(&inputs)
+                )
+            );
+        }
+    }
+}
@@ -1,3 +1,25 @@
+// Lets `#[derive(TruncatedDebug)]` output, which names `::strings`, compile inside this crate.
+extern crate self as strings;
+
+pub mod debug_budget_truncation;
+pub mod debug_builders_truncation;
+pub mod debug_char_truncation;
 pub mod debug_exact_size_truncation;
 pub mod debug_exact_size_truncation_playground;
+pub mod debug_filtered_truncation;
+pub mod debug_iterator_truncation;
+pub mod debug_native_shape_truncation;
+pub mod debug_nested_truncation;
+pub mod debug_owned_truncation;
+pub mod debug_recursive_truncation;
+pub mod debug_run_length_truncation;
+pub mod debug_sampled_truncation;
 pub mod debug_slice_truncation;
+pub mod debug_sorted_truncation;
+pub mod debug_structure_truncation;
+pub mod debug_top_k_truncation;
+pub mod debug_truncation;
+pub mod debug_truncation_derive;
+pub mod debug_truncation_ext;
+pub mod debug_truncation_options;
+pub mod prelude;
@@ -0,0 +1,12 @@
+//! Extension traits adding `.truncated_debug()`, `.truncated_debug_to::<N>()` and `.truncated_debug_with(&options)`
+//! to slices, std collections and exact-size iterators, `.field_truncated(...)` to `DebugStruct`
+//! and `.debug_list_truncated(...)` and `.debug_map_truncated(...)` to `Formatter`, along with the options they accept.
+//! ```rust
+//! use strings::prelude::*;
+//! let data = (1..=50).collect::<Vec<_>>();
+//! assert!(format!("{:?}", data.truncated_debug()).starts_with("(length=50 truncated to 42) [1, 2"));
+//! ```
+
+pub use crate::debug_builders_truncation::{DebugStructTruncateExt, FormatterTruncateExt};
+pub use crate::debug_truncation_ext::{TruncatedDebugExt, TruncatedDebugIteratorExt};
+pub use crate::debug_truncation_options::TruncationOptions;
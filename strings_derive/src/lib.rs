@@ -0,0 +1,255 @@
+//! `#[derive(TruncatedDebug)]` for the `strings` crate, re-exported as
+//! `strings::debug_truncation_derive::TruncatedDebug`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Field, Fields, GenericParam,
+    Index, LitInt,
+};
+
+/// Derive a `Debug` impl matching `#[derive(Debug)]`, except for fields marked with `#[truncate(...)]`:
+/// - `#[truncate]` truncates the collection to `DEFAULT_DEBUG_FMT_MAX_ITEMS` items.
+/// - `#[truncate(max = 10)]` truncates the collection to 10 items.
+/// - `#[truncate(head = 3, tail = 2)]` shows the first 3 and the last 2 items.
+/// - `#[truncate(count_only)]` shows only the length, e.g. `(length=5)`.
+/// - `#[truncate(skip)]` leaves the field out.
+///
+/// A format precision, e.g. `{:.5?}`, replaces the item limit of every truncated field,
+/// keeping its tail as `TruncationOptions` does for any other truncating holder.
+/// Truncated fields must implement `NativeShapeTruncateDebug`.
+#[proc_macro_derive(TruncatedDebug, attributes(truncate))]
+pub fn derive_truncated_debug(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_truncated_debug(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// How one field is written, as given by its `#[truncate(...)]` attribute.
+enum FieldTruncation {
+    Untruncated,
+    Skip,
+    CountOnly,
+    Options(TokenStream2),
+}
+
+fn expand_truncated_debug(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let name_string = name.to_string();
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "TruncatedDebug can only be derived for structs",
+            ))
+        }
+    };
+
+    let body = match fields {
+        Fields::Named(named) => {
+            let mut field_calls = Vec::new();
+            for field in &named.named {
+                let ident = field.ident.as_ref().expect("named field");
+                let field_name = ident.to_string();
+                if let Some(value) = field_value(field, quote!(self.#ident))? {
+                    field_calls.push(quote!(debug_struct.field(#field_name, #value);));
+                }
+            }
+            quote! {
+                let mut debug_struct = f.debug_struct(#name_string);
+                #(#field_calls)*
+                debug_struct.finish()
+            }
+        }
+        Fields::Unnamed(unnamed) => {
+            let mut field_calls = Vec::new();
+            for (index, field) in unnamed.unnamed.iter().enumerate() {
+                let index = Index::from(index);
+                if let Some(value) = field_value(field, quote!(self.#index))? {
+                    field_calls.push(quote!(debug_tuple.field(#value);));
+                }
+            }
+            quote! {
+                let mut debug_tuple = f.debug_tuple(#name_string);
+                #(#field_calls)*
+                debug_tuple.finish()
+            }
+        }
+        Fields::Unit => quote!(f.write_str(#name_string)),
+    };
+
+    let type_params = input
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(type_param) => Some(type_param.ident.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let where_clause = input.generics.make_where_clause();
+    for type_param in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#type_param: ::core::fmt::Debug));
+    }
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #type_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+/// The expression passed to `field` for `field`, or `None` if it is skipped.
+fn field_value(field: &Field, access: TokenStream2) -> syn::Result<Option<TokenStream2>> {
+    let value = match field_truncation(&field.attrs)? {
+        FieldTruncation::Untruncated => Some(quote!(&#access)),
+        FieldTruncation::Skip => None,
+        FieldTruncation::CountOnly => Some(quote! {
+            &::strings::debug_truncation_derive::CountOnlyField(&#access)
+        }),
+        FieldTruncation::Options(options) => Some(quote! {
+            &::strings::debug_truncation_derive::TruncatedField::new(&#access, #options)
+        }),
+    };
+
+    Ok(value)
+}
+
+fn field_truncation(attrs: &[Attribute]) -> syn::Result<FieldTruncation> {
+    let mut truncation = FieldTruncation::Untruncated;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("truncate")) {
+        if !matches!(truncation, FieldTruncation::Untruncated) {
+            return Err(syn::Error::new(
+                attr.span(),
+                "duplicate #[truncate] attribute",
+            ));
+        }
+        truncation = parse_truncate_attribute(attr)?;
+    }
+
+    Ok(truncation)
+}
+
+fn parse_truncate_attribute(attr: &Attribute) -> syn::Result<FieldTruncation> {
+    let options = quote!(::strings::debug_truncation_options::TruncationOptions::new());
+    if let syn::Meta::Path(_) = attr.meta {
+        return Ok(FieldTruncation::Options(options));
+    }
+
+    let mut max: Option<LitInt> = None;
+    let mut head: Option<LitInt> = None;
+    let mut tail: Option<LitInt> = None;
+    let mut skip = false;
+    let mut count_only = false;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("max") {
+            max = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("head") {
+            head = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("tail") {
+            tail = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("skip") {
+            skip = true;
+        } else if meta.path.is_ident("count_only") {
+            count_only = true;
+        } else {
+            return Err(meta.error("expected `max`, `head`, `tail`, `skip` or `count_only`"));
+        }
+        Ok(())
+    })?;
+
+    let limits_given = max.is_some() || head.is_some() || tail.is_some();
+    match (skip, count_only) {
+        (true, true) => Err(syn::Error::new(
+            attr.span(),
+            "`skip` and `count_only` cannot be combined",
+        )),
+        (true, false) | (false, true) if limits_given => Err(syn::Error::new(
+            attr.span(),
+            "`skip` and `count_only` cannot be combined with `max`, `head` or `tail`",
+        )),
+        (true, false) => Ok(FieldTruncation::Skip),
+        (false, true) => Ok(FieldTruncation::CountOnly),
+        (false, false) => match (max, head, tail) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => Err(syn::Error::new(
+                attr.span(),
+                "`max` cannot be combined with `head` or `tail`",
+            )),
+            (Some(max), None, None) => {
+                Ok(FieldTruncation::Options(quote!(#options.max_length(#max))))
+            }
+            (None, None, None) => Ok(FieldTruncation::Options(options)),
+            (None, head, tail) => {
+                let head = head.map_or_else(|| quote!(0), |head| quote!(#head));
+                let tail = tail.map_or_else(|| quote!(0), |tail| quote!(#tail));
+                Ok(FieldTruncation::Options(
+                    quote!(#options.head_and_tail(#head, #tail)),
+                ))
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod test_expand_truncated_debug {
+        use super::super::expand_truncated_debug;
+        use syn::{parse_quote, DeriveInput};
+
+        fn expand_error_for(input: DeriveInput) -> String {
+            expand_truncated_debug(input)
+                .expect_err("expected a compile error")
+                .to_string()
+        }
+
+        #[test]
+        fn enum_rejected() {
+            let result = expand_error_for(parse_quote! {
+                enum Enum {
+                    A(Vec<i32>),
+                }
+            });
+
+            assert_eq!(result, "TruncatedDebug can only be derived for structs");
+        }
+
+        #[test]
+        fn count_only_with_max_rejected() {
+            let result = expand_error_for(parse_quote! {
+                struct Struct {
+                    #[truncate(count_only, max = 2)]
+                    vec: Vec<i32>,
+                }
+            });
+
+            assert_eq!(
+                result,
+                "`skip` and `count_only` cannot be combined with `max`, `head` or `tail`"
+            );
+        }
+
+        #[test]
+        fn unknown_option_rejected() {
+            let result = expand_error_for(parse_quote! {
+                struct Struct {
+                    #[truncate(length = 2)]
+                    vec: Vec<i32>,
+                }
+            });
+
+            assert_eq!(
+                result,
+                "expected `max`, `head`, `tail`, `skip` or `count_only`"
+            );
+        }
+    }
+}